@@ -0,0 +1,361 @@
+//! ✅ columnar.rs
+//!
+//! parquet 출력
+//! - 레코드를 row group 크기만큼 모았다가 Arrow RecordBatch 로 변환해 하나의 row group 으로 기록
+//!   (파일 전체를 메모리에 올리지 않음)
+//! - 스키마는 `schema { ... }` 로 선언하거나, 없으면 첫 row group 의 레코드에서 추론
+//!   - 중첩 객체 → struct, 배열 → list, 정수와 실수가 섞이면 실수, 그 밖의 스칼라 충돌은 문자열
+//!   - 추론한 스키마에 없는 키가 나중 레코드에 나오면 버리고 한 번만 경고 (선언한 스키마면 조용히 버림)
+//!   - 나중 레코드의 값이 추론한 타입과 맞지 않으면 에러 (추론한 레코드 수와 `schema { ... }` 선언을 안내)
+//! - 압축은 `compress "zstd"|"gzip"|"none"` (기본값 snappy), parquet 내부 압축으로 처리
+
+use crate::compression::{Compression, FinishWrite};
+use crate::evaluator::Record;
+use crate::format::{ColumnType, ParquetOptions};
+
+use arrow_json::ReaderBuilder;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+
+use std::sync::Arc;
+
+/// ✅ 기본 row group 크기 (레코드 수)
+const DEFAULT_ROW_GROUP_SIZE: usize = 8192;
+
+/// ✅ parquet 인코더
+pub struct ParquetEncoder {
+    state: WriterState,
+    properties: Option<WriterProperties>, // 작성기를 만들 때 한 번 사용
+    declared: bool,                       // 스키마를 선언했는지 (추론한 경우 모르는 키는 경고)
+    inferred_from: usize,                 // 스키마를 추론한 레코드 수 (에러 메시지용)
+    warned: bool,                         // 모르는 키 경고를 이미 했는지
+    pending: Vec<Record>,                 // 아직 기록하지 않은 레코드
+    row_group_size: usize,
+}
+
+/// ✅ 작성기 상태 (스키마가 정해지기 전에는 스트림만 보관)
+enum WriterState {
+    Waiting(Box<dyn FinishWrite>, Option<SchemaRef>),
+    Writing(Box<ArrowWriter<Box<dyn FinishWrite>>>, SchemaRef),
+    Done,
+}
+
+impl ParquetEncoder {
+    /// 🔹 인코더 생성 (압축 방식과 레벨은 parquet 내부 압축으로 적용)
+    pub fn new(
+        writer: Box<dyn FinishWrite>,
+        options: &ParquetOptions,
+        compression: Option<Compression>,
+        level: Option<i32>,
+    ) -> Result<Self, String> {
+        let codec = match (compression, level) {
+            (None, None) => parquet::basic::Compression::SNAPPY,
+            (None | Some(Compression::None), Some(_)) => {
+                return Err("Compression level requires a compression method".to_string());
+            }
+            (Some(Compression::None), None) => parquet::basic::Compression::UNCOMPRESSED,
+            (Some(Compression::Zstd), _) => {
+                let level = ZstdLevel::try_new(level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))
+                    .map_err(|e| format!("Invalid Zstd compression level: {}", e))?;
+                parquet::basic::Compression::ZSTD(level)
+            }
+            (Some(Compression::Gzip), _) => {
+                let level = GzipLevel::try_new(level.map_or(6, |l| l.max(0) as u32))
+                    .map_err(|e| format!("Invalid Gzip compression level: {}", e))?;
+                parquet::basic::Compression::GZIP(level)
+            }
+            (Some(other), _) => {
+                return Err(format!("{:?} compression is not supported for parquet output", other));
+            }
+        };
+
+        let row_group_size = options.row_group_size.unwrap_or(DEFAULT_ROW_GROUP_SIZE);
+        let properties = WriterProperties::builder()
+            .set_compression(codec)
+            .set_max_row_group_size(row_group_size)
+            .build();
+        let schema = options.schema.as_ref().map(|fields| Arc::new(Schema::new(declared_fields(fields))));
+
+        Ok(Self {
+            state: WriterState::Waiting(writer, schema),
+            properties: Some(properties),
+            declared: options.schema.is_some(),
+            inferred_from: 0,
+            warned: false,
+            pending: Vec::with_capacity(row_group_size),
+            row_group_size,
+        })
+    }
+
+    /// 🔹 레코드 하나 추가 (row group 크기가 차면 기록)
+    pub fn write(&mut self, record: &Record) -> Result<(), String> {
+        self.pending.push(record.clone());
+        if self.pending.len() >= self.row_group_size {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// 🔹 남은 레코드와 footer 기록 후 내부 스트림 반환
+    pub fn finish(mut self) -> Result<Box<dyn FinishWrite>, String> {
+        self.flush_row_group()?;
+        if let WriterState::Waiting(..) = self.state {
+            // 레코드가 하나도 없음: 선언한 (없으면 빈) 스키마로 빈 파일 기록
+            self.start_writer()?;
+        }
+
+        match std::mem::replace(&mut self.state, WriterState::Done) {
+            WriterState::Writing(writer, _) => writer
+                .into_inner()
+                .map_err(|e| format!("Failed to finish parquet file: {}", e)),
+            _ => unreachable!("parquet writer is started above"),
+        }
+    }
+
+    /// 🔹 모아 둔 레코드를 하나의 row group 으로 기록
+    fn flush_row_group(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if let WriterState::Waiting(..) = self.state {
+            self.start_writer()?;
+        }
+        let WriterState::Writing(writer, schema) = &mut self.state else {
+            unreachable!("parquet writer is started above");
+        };
+
+        let mut decoder = ReaderBuilder::new(schema.clone())
+            .with_batch_size(self.pending.len())
+            .with_coerce_primitive(true)
+            .build_decoder()
+            .map_err(|e| format!("Failed to prepare parquet row group: {}", e))?;
+
+        // 추론한 스키마면 에러에 한계를 덧붙임 (스키마는 첫 row group 에서만 정해짐)
+        let inferred = match self.declared {
+            true => String::new(),
+            false => format!("the schema was inferred from the first {} record(s); ", self.inferred_from),
+        };
+        let hint = match self.declared {
+            true => String::new(),
+            false => format!(" ({}declare it with `schema {{ ... }}`)", inferred),
+        };
+        for record in &self.pending {
+            for field in schema.fields() {
+                if let Some(value) = record.get(field.name()) {
+                    check_integer(value, field.data_type(), field.name()).map_err(|e| {
+                        format!("{} ({}declare it as float with `schema {{ ... }}`)", e, inferred)
+                    })?;
+                }
+            }
+            if !self.declared
+                && !self.warned
+                && let Some(key) = unknown_key(record, schema.fields())
+            {
+                eprintln!(
+                    "⚠️ Parquet column '{}' is not in the schema inferred from the first {} record(s) and is dropped (declare it with `schema {{ ... }}`)",
+                    key, self.inferred_from
+                );
+                self.warned = true;
+            }
+        }
+        decoder
+            .serialize(&self.pending)
+            .map_err(|e| format!("Record does not match the parquet schema: {}{}", e, hint))?;
+        if let Some(batch) = decoder
+            .flush()
+            .map_err(|e| format!("Record does not match the parquet schema: {}{}", e, hint))?
+        {
+            writer
+                .write(&batch)
+                .and_then(|_| writer.flush())
+                .map_err(|e| format!("Failed to write parquet row group: {}", e))?;
+        }
+
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// 🔹 스키마 확정 (선언이 없으면 모아 둔 레코드에서 추론) 후 작성기 생성
+    fn start_writer(&mut self) -> Result<(), String> {
+        let WriterState::Waiting(sink, schema) = std::mem::replace(&mut self.state, WriterState::Done) else {
+            return Ok(());
+        };
+        let schema = match schema {
+            Some(schema) => schema,
+            None => {
+                self.inferred_from = self.pending.len();
+                Arc::new(infer_schema(&self.pending)?)
+            }
+        };
+
+        let writer = ArrowWriter::try_new(sink, schema.clone(), self.properties.take())
+            .map_err(|e| format!("Failed to start parquet file: {}", e))?;
+        self.state = WriterState::Writing(Box::new(writer), schema);
+        Ok(())
+    }
+}
+
+// ==========================================================
+// ✅ 스키마
+// ==========================================================
+
+/// 🔧 정수 열에 실수가 들어오지 않았는지 확인 (Arrow 디코더는 소수점 이하를 조용히 버림)
+fn check_integer(value: &Value, data_type: &DataType, path: &str) -> Result<(), String> {
+    match (value, data_type) {
+        (Value::Number(n), DataType::Int64) if !n.is_i64() => {
+            Err(format!("Parquet column '{}' is an integer column, but found {}", path, n))
+        }
+        (Value::Array(items), DataType::List(item)) => items
+            .iter()
+            .try_for_each(|v| check_integer(v, item.data_type(), path)),
+        (Value::Object(map), DataType::Struct(fields)) => fields.iter().try_for_each(|field| {
+            match map.get(field.name()) {
+                Some(v) => check_integer(v, field.data_type(), &format!("{}.{}", path, field.name())),
+                None => Ok(()),
+            }
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// 🔧 스키마에 없는 키 (중첩 객체는 `a.b` 경로, 없으면 None)
+fn unknown_key<'a>(map: impl IntoIterator<Item = (&'a String, &'a Value)>, fields: &Fields) -> Option<String> {
+    map.into_iter().find_map(|(key, value)| {
+        let Some(field) = fields.iter().find(|f| f.name() == key) else {
+            return Some(key.clone());
+        };
+        match (value, field.data_type()) {
+            (Value::Object(map), DataType::Struct(children)) => {
+                unknown_key(map, children).map(|child| format!("{}.{}", key, child))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// 🔧 선언한 열 목록 → Arrow 필드 (모두 null 허용)
+fn declared_fields(fields: &[(String, ColumnType)]) -> Fields {
+    fields
+        .iter()
+        .map(|(name, column_type)| Field::new(name, declared_type(column_type), true))
+        .collect()
+}
+
+/// 🔧 선언한 열 타입 → Arrow 타입
+fn declared_type(column_type: &ColumnType) -> DataType {
+    match column_type {
+        ColumnType::String => DataType::Utf8,
+        ColumnType::Int => DataType::Int64,
+        ColumnType::Float => DataType::Float64,
+        ColumnType::Bool => DataType::Boolean,
+        ColumnType::List(item) => DataType::new_list(declared_type(item), true),
+        ColumnType::Struct(fields) => DataType::Struct(declared_fields(fields)),
+    }
+}
+
+/// 🔧 레코드들에서 스키마 추론 (열 순서는 처음 나온 순서)
+fn infer_schema(records: &[Record]) -> Result<Schema, String> {
+    let mut fields: Vec<(String, DataType)> = Vec::new();
+    for record in records {
+        for (key, value) in record {
+            merge_field(&mut fields, key, value)?;
+        }
+    }
+    Ok(Schema::new(finish_fields(fields)))
+}
+
+/// 🔧 필드 목록에 값 하나의 타입을 합침
+fn merge_field(fields: &mut Vec<(String, DataType)>, key: &str, value: &Value) -> Result<(), String> {
+    let inferred = infer_type(value)?;
+    match fields.iter_mut().find(|(name, _)| name == key) {
+        Some((_, existing)) => {
+            *existing = merge_types(existing, &inferred)
+                .ok_or_else(|| format!("Conflicting types for parquet column '{}': {} and {}", key, existing, inferred))?;
+        }
+        None => fields.push((key.to_string(), inferred)),
+    }
+    Ok(())
+}
+
+/// 🔧 값 하나의 Arrow 타입 (null 은 DataType::Null → 다른 값과 합치거나 마지막에 문자열)
+fn infer_type(value: &Value) -> Result<DataType, String> {
+    Ok(match value {
+        Value::Null => DataType::Null,
+        Value::Bool(_) => DataType::Boolean,
+        Value::Number(n) if n.is_i64() => DataType::Int64,
+        Value::Number(_) => DataType::Float64,
+        Value::String(_) => DataType::Utf8,
+        Value::Array(items) => {
+            let mut item_type = DataType::Null;
+            for item in items {
+                let inferred = infer_type(item)?;
+                item_type = merge_types(&item_type, &inferred)
+                    .ok_or_else(|| format!("Array mixes incompatible types: {} and {}", item_type, inferred))?;
+            }
+            DataType::new_list(item_type, true)
+        }
+        Value::Object(map) => {
+            let mut fields = Vec::new();
+            for (key, value) in map {
+                merge_field(&mut fields, key, value)?;
+            }
+            DataType::Struct(fields.into_iter().map(|(name, t)| Field::new(name, t, true)).collect())
+        }
+    })
+}
+
+/// 🔧 두 타입을 모두 담을 수 있는 타입 (불가능하면 None)
+fn merge_types(a: &DataType, b: &DataType) -> Option<DataType> {
+    match (a, b) {
+        (a, b) if a == b => Some(a.clone()),
+        (DataType::Null, other) | (other, DataType::Null) => Some(other.clone()),
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => Some(DataType::Float64),
+        (DataType::List(a), DataType::List(b)) => {
+            Some(DataType::new_list(merge_types(a.data_type(), b.data_type())?, true))
+        }
+        (DataType::Struct(a), DataType::Struct(b)) => {
+            let mut fields: Vec<(String, DataType)> =
+                a.iter().map(|f| (f.name().clone(), f.data_type().clone())).collect();
+            for field in b {
+                match fields.iter_mut().find(|(name, _)| name == field.name()) {
+                    Some((_, existing)) => *existing = merge_types(existing, field.data_type())?,
+                    None => fields.push((field.name().clone(), field.data_type().clone())),
+                }
+            }
+            Some(DataType::Struct(fields.into_iter().map(|(name, t)| Field::new(name, t, true)).collect()))
+        }
+        (a, b) if is_scalar(a) && is_scalar(b) => Some(DataType::Utf8),
+        _ => None,
+    }
+}
+
+/// 🔧 스칼라 타입 여부 (서로 충돌하면 문자열로 기록)
+fn is_scalar(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Boolean | DataType::Int64 | DataType::Float64 | DataType::Utf8)
+}
+
+/// 🔧 추론이 끝난 필드 목록 → Arrow 필드 (끝까지 null 뿐인 열은 문자열)
+fn finish_fields(fields: Vec<(String, DataType)>) -> Fields {
+    fields
+        .into_iter()
+        .map(|(name, data_type)| Field::new(name, finish_type(data_type), true))
+        .collect()
+}
+
+/// 🔧 남은 DataType::Null 을 문자열로 바꿈
+fn finish_type(data_type: DataType) -> DataType {
+    match data_type {
+        DataType::Null => DataType::Utf8,
+        DataType::List(item) => DataType::new_list(finish_type(item.data_type().clone()), true),
+        DataType::Struct(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|f| Field::new(f.name(), finish_type(f.data_type().clone()), true))
+                .collect(),
+        ),
+        other => other,
+    }
+}
@@ -0,0 +1,172 @@
+//! ✅ compression.rs
+//!
+//! 입출력 스트림의 압축 처리 (gzip / zstd / bzip2)
+//! - 입력: 매직 바이트로 감지, 감지되지 않으면 확장자로 판단
+//! - 출력: 확장자(.gz, .zst, .bz2)로 판단, output 의 `compress "..."` 로 직접 지정 가능
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use bzip2::read::MultiBzDecoder;
+use bzip2::write::BzEncoder;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+/// ✅ 지원하는 압축 방식
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// 🔹 이름으로 찾기 (`compress "zstd"` 등)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(Compression::None),
+            "gzip" | "gz" => Some(Compression::Gzip),
+            "zstd" | "zst" => Some(Compression::Zstd),
+            "bzip2" | "bz2" => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// 🔹 파일 확장자로 판단
+    pub fn from_extension(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            Some("bz2") => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    /// 🔹 스트림 앞부분의 매직 바이트로 판단
+    fn from_magic(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if bytes.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+
+    /// 🔹 레벨 지정이 가능한 범위
+    fn level_range(self) -> Option<(i32, i32)> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some((0, 9)),
+            Compression::Zstd => Some((1, 22)),
+            Compression::Bzip2 => Some((1, 9)),
+        }
+    }
+}
+
+/// 🔧 입력 스트림을 (필요하면) 압축 해제 스트림으로 감쌈
+///
+/// 매직 바이트를 먼저 확인하고, 알 수 없으면 `path` 의 확장자로 판단
+pub fn decompress_reader<R: BufRead + 'static>(
+    mut reader: R,
+    path: &str,
+) -> io::Result<Box<dyn BufRead>> {
+    let compression = match Compression::from_magic(reader.fill_buf()?) {
+        Compression::None => Compression::from_extension(path),
+        detected => detected,
+    };
+
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+    })
+}
+
+/// ✅ 마무리(finish) 단계가 필요한 출력 스트림 (parquet 작성기로 넘길 수 있도록 Send)
+pub trait FinishWrite: Write + Send {
+    /// 🔹 남은 데이터와 압축 트레일러를 모두 기록
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// 🔹 압축하지 않는 출력
+struct Plain<W: Write>(W);
+
+impl<W: Write> Write for Plain<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: Write + Send> FinishWrite for Plain<W> {
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: Write + Send> FinishWrite for GzEncoder<W> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        GzEncoder::finish(*self)?.flush()
+    }
+}
+
+impl<W: Write + Send> FinishWrite for zstd::Encoder<'static, W> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        zstd::Encoder::finish(*self)?.flush()
+    }
+}
+
+impl<W: Write + Send> FinishWrite for BzEncoder<W> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        BzEncoder::finish(*self)?.flush()
+    }
+}
+
+/// 🔧 출력 스트림을 압축 스트림으로 감쌈 (`level` 이 없으면 각 방식의 기본값)
+pub fn compress_writer<W: Write + Send + 'static>(
+    writer: W,
+    compression: Compression,
+    level: Option<i32>,
+) -> Result<Box<dyn FinishWrite>, String> {
+    if let Some(level) = level {
+        match compression.level_range() {
+            Some((min, max)) if (min..=max).contains(&level) => {}
+            Some((min, max)) => {
+                return Err(format!(
+                    "Invalid {:?} compression level {} (expected {}-{})",
+                    compression, level, min, max
+                ));
+            }
+            None => return Err("Compression level requires a compression method".to_string()),
+        }
+    }
+
+    Ok(match compression {
+        Compression::None => Box::new(Plain(writer)),
+        Compression::Gzip => {
+            let level = level.map_or(flate2::Compression::default(), |l| flate2::Compression::new(l as u32));
+            Box::new(GzEncoder::new(writer, level))
+        }
+        Compression::Zstd => Box::new(
+            zstd::Encoder::new(writer, level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))
+                .map_err(|e| format!("Failed to start zstd compression: {}", e))?,
+        ),
+        Compression::Bzip2 => {
+            let level = level.map_or(bzip2::Compression::default(), |l| bzip2::Compression::new(l as u32));
+            Box::new(BzEncoder::new(writer, level))
+        }
+    })
+}
@@ -0,0 +1,87 @@
+//! ✅ diagnostic.rs
+//!
+//! 위치 정보를 포함한 에러(Diagnostic) 정의 및 출력
+//! 출력 예시:
+//!     script.jdl:7:23: Expected token Semicolon, but found RBrace
+//!        |
+//!      7 |     content = @문제.prefix("문제: ")
+//!        |                       ^
+
+use crate::span::Span;
+use std::fmt;
+
+/// ✅ 파서/런타임 에러
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>, // 위치를 알 수 없는 에러 (파일 I/O 등)는 None
+}
+
+impl Diagnostic {
+    /// 🔹 위치가 있는 에러 생성
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span: Some(span),
+        }
+    }
+
+    /// 🔹 위치가 있을 수도 없을 수도 있는 에러 생성 (명령줄 옵션 등)
+    pub fn at(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// 🔹 `파일:줄:열: 메시지` + 해당 줄 발췌 + 캐럿(^) 밑줄로 렌더링
+    pub fn render(&self, path: &str, source: &str) -> String {
+        let Some(span) = self.span else {
+            return format!("{}: {}", path, self.message);
+        };
+
+        let mut out = format!("{}:{}:{}: {}", path, span.line, span.column, self.message);
+
+        let Some(line_text) = source.lines().nth(span.line.saturating_sub(1)) else {
+            return out;
+        };
+        let line_text = line_text.trim_end_matches('\r');
+
+        // 밑줄 길이: span이 걸친 문자 수 (여러 줄에 걸치면 해당 줄 끝까지)
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = line_start + line_text.len();
+        let underline_end = span.end.clamp(start, line_end);
+        let width = source
+            .get(start..underline_end)
+            .map_or(0, |s| s.chars().count())
+            .max(1);
+
+        let gutter = span.line.to_string().len();
+        out.push_str(&format!("\n{} |", " ".repeat(gutter)));
+        out.push_str(&format!("\n{} | {}", span.line, line_text));
+        out.push_str(&format!(
+            "\n{} | {}{}",
+            " ".repeat(gutter),
+            " ".repeat(span.column.saturating_sub(1)),
+            "^".repeat(width)
+        ));
+
+        out
+    }
+}
+
+impl From<String> for Diagnostic {
+    fn from(message: String) -> Self {
+        Self { message, span: None }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}:{}: {}", span.line, span.column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -1,63 +1,106 @@
 //! ✅ evaluator.rs
 //!
 //! 이 모듈은 transform 명령어의 우변에 등장하는 Expression을 실제 JSON 값으로 평가한다.
-//! - 문자열 기반 표현식은 Value::String("...") 형태로 반환
+//! - 수정자 없는 필드 참조는 원본 JSON 값을 타입 그대로 복사 (숫자, bool, 배열, 객체 등)
+//! - 문자열 연결(+)이나 prefix/suffix/default 수정자가 붙을 때만 문자열로 변환
+//! - 수정자(메서드)는 어떤 식에든 붙일 수 있고 왼쪽부터 하나씩 차례로 적용
+//!   (`@a.prefix("x").default("y")` 는 빈 값이면 "y", 길이와 위치는 문자 단위라 한글도 한 글자)
+//! - 정규식 수정자 (regex_replace, regex_match, regex_extract, regex_split) 는 파싱할 때 컴파일한 패턴 사용
+//! - 산술 연산(+ - * / %)은 숫자끼리만 허용, null 이 섞이면 결과도 null
+//! - 비교(== != < <= > >=), 논리(&& || !), 조건식(?:, if/then/else) 지원
 //! - raw()는 JSON 객체 그대로 Value::Object(...)로 반환
-//! - serial()은 1부터 자동으로 증가하는 문자열 숫자
+//! - serial()은 1부터 자동으로 증가하는 JSON 숫자 (호출 위치마다 따로 셈)
+//!   - `serial(start: 1000, step: 5)`, `pad: 6` / `prefix: "Q"` 를 주면 문자열 ("Q001000")
+//!   - `serial("train")` 은 같은 이름끼리 번호를 공유하는 카운터, `serial(by: @category)` 는 값마다 1부터 다시 셈
+//! - source_file(), source_line()은 현재 레코드가 읽힌 입력 파일 경로와 줄 번호
 
-use crate::parser::{Expression, FieldWithModifiers, FieldModifier};
-use crate::utils::unescape_string;
+use crate::diagnostic::Diagnostic;
+use crate::parser::{BinaryOp, Expression, Method, SerialCounter, SerialSpec, UnaryOp};
+use crate::span::Spanned;
 use indexmap::IndexMap;
 use serde_json::{Value, Map};
 
-/// ✅ serial()을 위한 상태 저장 구조체
+use std::collections::HashMap;
+
+/// ✅ repeat / pad_left 결과 문자열의 최대 크기 (바이트, 넘으면 메모리를 잡기 전에 에러)
+const MAX_METHOD_RESULT_LEN: usize = 1 << 28;
+
+/// ✅ JSONL 한 줄에 해당하는 레코드 (키 순서 유지)
+pub type Record = IndexMap<String, Value>;
+
+/// ✅ 평가 상태 (serial 카운터, 현재 레코드의 출처)
 #[derive(Default)]
 pub struct EvaluatorState {
-    pub serial_counter: usize,
+    pub serial_counters: HashMap<(SerialCounter, Option<String>), i64>, // (카운터, by 값의 JSON 표현) → 지금까지 매긴 번호 수
+    pub source_file: String, // 현재 레코드의 입력 파일 경로
+    pub source_line: usize,  // 현재 레코드의 줄 번호 (1부터)
 }
 
 impl EvaluatorState {
     pub fn new() -> Self {
-        Self { serial_counter: 1 }
+        Self::default()
     }
 }
 
 /// 🔍 표현식을 평가하여 JSON Value로 변환
 ///
 /// # Params
-/// - `expr`: 파싱된 Expression
+/// - `expr`: 파싱된 Expression (위치 정보 포함)
 /// - `record`: 한 줄의 JSONL 데이터 (IndexMap<String, Value>)
 /// - `state`: serial 카운터를 위한 상태 구조체
 pub fn evaluate_expression(
-    expr: &Expression,
-    record: &IndexMap<String, Value>,
+    expr: &Spanned<Expression>,
+    record: &Record,
     state: &mut EvaluatorState,
-) -> Result<Value, String> {
-    match expr {
-        // 📌 문자열 리터럴
-        Expression::Literal(s) => Ok(Value::String(unescape_string(s))),
+) -> Result<Value, Diagnostic> {
+    match &expr.node {
+        // 📌 리터럴 (문자열, 숫자, bool, null)
+        Expression::Literal(value) => Ok(value.clone()),
 
-        // 📌 일반 필드 (@meta.score 등)
+        // 📌 일반 필드 (@meta.score 등) → 원본 타입 유지, 없으면 null
         Expression::FieldPath(path) => {
-            let value = get_nested_value_as_string(record, path);
-            Ok(Value::String(value.unwrap_or_default()))
+            let value = get_nested_value(record, path);
+            Ok(value.cloned().unwrap_or(Value::Null))
+        }
+
+        // 📌 메서드 호출 (@a.prefix("x"), ("a" + @b).upper(), serial().pad_left(6, "0"))
+        //    receiver 를 먼저 평가하므로 연속된 호출은 왼쪽부터 차례로 적용됨
+        Expression::MethodCall { receiver, method } => {
+            let value = evaluate_expression(receiver, record, state)?;
+            apply_method(&value, method).map_err(|e| Diagnostic::new(e, expr.span))
         }
 
-        // 📌 필드 + 수정자 (prefix, suffix, default)
-        Expression::FieldWithModifiers(field_struct) => {
-            let value = evaluate_field_with_modifiers(field_struct, record)?;
-            Ok(Value::String(value))
+        // 📌 논리 연산 (&&, ||) → 단락 평가, 결과는 bool
+        Expression::Binary { op: op @ (BinaryOp::And | BinaryOp::Or), left, right } => {
+            let l = is_truthy(&evaluate_expression(left, record, state)?);
+            if (*op == BinaryOp::And && !l) || (*op == BinaryOp::Or && l) {
+                return Ok(Value::Bool(l));
+            }
+            let r = evaluate_expression(right, record, state)?;
+            Ok(Value::Bool(is_truthy(&r)))
         }
 
-        // 📌 여러 표현식 연결
-        Expression::Concat(parts) => {
-            let mut result = String::new();
-            for part in parts {
-                let v = evaluate_expression(part, record, state)?;
-                let s = v.as_str().unwrap_or("").to_string();
-                result.push_str(&s);
+        // 📌 이항 연산 (산술, 비교)
+        Expression::Binary { op, left, right } => {
+            let l = evaluate_expression(left, record, state)?;
+            let r = evaluate_expression(right, record, state)?;
+            apply_binary(*op, &l, &r).map_err(|e| Diagnostic::new(e, expr.span))
+        }
+
+        // 📌 조건식 (cond ? a : b, if cond then a else b)
+        Expression::Conditional { condition, then_branch, else_branch } => {
+            let cond = evaluate_expression(condition, record, state)?;
+            if is_truthy(&cond) {
+                evaluate_expression(then_branch, record, state)
+            } else {
+                evaluate_expression(else_branch, record, state)
             }
-            Ok(Value::String(result))
+        }
+
+        // 📌 단항 연산 (-x, !x)
+        Expression::Unary { op, operand } => {
+            let v = evaluate_expression(operand, record, state)?;
+            apply_unary(*op, &v).map_err(|e| Diagnostic::new(e, expr.span))
         }
 
         // ✅ raw() → 전체 객체 반환
@@ -66,61 +109,326 @@ pub fn evaluate_expression(
             Ok(Value::Object(map))
         }
 
-        // ✅ serial() → 자동 증가 문자열 반환
-        Expression::Serial => {
-            let result = state.serial_counter.to_string();
-            state.serial_counter += 1;
-            Ok(Value::String(result))
+        // ✅ serial() → 자동 증가 번호 반환
+        Expression::Serial(spec) => {
+            let group = match &spec.by {
+                // JSON 표현으로 구분 (null 과 "", 1 과 "1" 은 서로 다른 그룹)
+                Some(by) => Some(evaluate_expression(by, record, state)?.to_string()),
+                None => None,
+            };
+            let issued = state
+                .serial_counters
+                .entry((spec.counter.clone(), group))
+                .or_insert(0);
+            let number = spec.start.saturating_add(issued.saturating_mul(spec.step));
+            *issued += 1;
+            Ok(format_serial(spec, number))
         }
+
+        // ✅ source_file() / source_line() → 현재 레코드의 출처
+        Expression::SourceFile => Ok(Value::from(state.source_file.clone())),
+        Expression::SourceLine => Ok(Value::from(state.source_line)),
     }
 }
 
-/// 🔍 FieldWithModifiers 를 평가하여 문자열로 반환
-fn evaluate_field_with_modifiers(
-    field: &FieldWithModifiers,
-    record: &IndexMap<String, Value>,
-) -> Result<String, String> {
-    // 경로 따라 실제 값 가져오기
-    let mut raw_value: Option<String> = get_nested_value_as_string(record, &field.path);
+/// 🔍 serial 번호 표현 (pad / prefix 가 없으면 숫자, 있으면 문자열)
+fn format_serial(spec: &SerialSpec, number: i64) -> Value {
+    if spec.pad.is_none() && spec.prefix.is_none() {
+        return Value::from(number);
+    }
+    let width = spec.pad.unwrap_or(0);
+    let prefix = spec.prefix.as_deref().unwrap_or_default();
+    Value::String(format!("{}{:0width$}", prefix, number, width = width))
+}
 
-    // 1️⃣ default() 우선 적용
-    for modifier in &field.modifiers {
-        if let FieldModifier::Default(default_str) = modifier {
-            if raw_value.is_none() || raw_value.as_deref() == Some("") {
-                raw_value = Some(unescape_string(default_str));
-            }
+/// ✅ 산술 연산용 숫자 표현 (정수는 가능한 한 정수로 유지)
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn from_value(value: &Value) -> Option<Self> {
+        let Value::Number(n) = value else {
+            return None;
+        };
+        n.as_i64().map(Number::Int).or_else(|| n.as_f64().map(Number::Float))
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(i) => i as f64,
+            Number::Float(f) => f,
         }
     }
 
-    let Some(mut value) = raw_value else {
-        return Ok(String::new());
+    fn into_value(self) -> Value {
+        match self {
+            Number::Int(i) => Value::from(i),
+            Number::Float(f) => Value::from(f),
+        }
+    }
+}
+
+/// 🔍 이항 연산 적용 (&&, || 는 evaluate_expression 에서 단락 평가)
+fn apply_binary(op: BinaryOp, l: &Value, r: &Value) -> Result<Value, String> {
+    match op {
+        BinaryOp::Add if l.is_string() || r.is_string() => {
+            Ok(Value::String(value_to_string(l) + &value_to_string(r)))
+        }
+        BinaryOp::Add => arithmetic(op, l, r, i64::checked_add, |x, y| x + y),
+        BinaryOp::Sub => arithmetic(op, l, r, i64::checked_sub, |x, y| x - y),
+        BinaryOp::Mul => arithmetic(op, l, r, i64::checked_mul, |x, y| x * y),
+        BinaryOp::Div => arithmetic(op, l, r, exact_div, |x, y| x / y),
+        BinaryOp::Rem => arithmetic(op, l, r, i64::checked_rem, |x, y| x % y),
+        BinaryOp::Eq => Ok(Value::Bool(values_equal(l, r))),
+        BinaryOp::Ne => Ok(Value::Bool(!values_equal(l, r))),
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => compare(op, l, r),
+        BinaryOp::And => Ok(Value::Bool(is_truthy(l) && is_truthy(r))),
+        BinaryOp::Or => Ok(Value::Bool(is_truthy(l) || is_truthy(r))),
+    }
+}
+
+/// 🔍 산술 연산
+/// - 숫자끼리만 허용, null 이 섞이면 결과는 null
+/// - 정수끼리는 정수 연산, 범위를 벗어나거나 실수가 섞이면 실수 연산
+fn arithmetic(
+    op: BinaryOp,
+    l: &Value,
+    r: &Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Value, String> {
+    if l.is_null() || r.is_null() {
+        return Ok(Value::Null);
+    }
+
+    let (Some(a), Some(b)) = (Number::from_value(l), Number::from_value(r)) else {
+        return Err(format!(
+            "Cannot apply '{}' to {} and {}",
+            binary_symbol(op),
+            type_name(l),
+            type_name(r)
+        ));
     };
 
-    if value.is_empty() {
-        return Ok(String::new());
+    if matches!(op, BinaryOp::Div | BinaryOp::Rem) && b.as_f64() == 0.0 {
+        return Err("Division by zero".to_string());
     }
 
-    // 2️⃣ prefix/suffix 적용
-    for modifier in &field.modifiers {
-        match modifier {
-            FieldModifier::Prefix(pre) => {
-                value = format!("{}{}", unescape_string(pre), value);
-            }
-            FieldModifier::Suffix(suf) => {
-                value = format!("{}{}", value, unescape_string(suf));
+    let result = match (a, b) {
+        (Number::Int(x), Number::Int(y)) => int_op(x, y).map(Number::Int),
+        _ => None,
+    };
+
+    Ok(result
+        .unwrap_or_else(|| Number::Float(float_op(a.as_f64(), b.as_f64())))
+        .into_value())
+}
+
+/// 🔍 정수 나눗셈: 나누어떨어질 때만 정수 결과 (아니면 None → 실수 나눗셈)
+fn exact_div(x: i64, y: i64) -> Option<i64> {
+    if x.checked_rem(y) == Some(0) {
+        x.checked_div(y)
+    } else {
+        None
+    }
+}
+
+/// 🔍 값 비교 (==, !=): 숫자는 정수/실수 구분 없이 값으로 비교
+fn values_equal(l: &Value, r: &Value) -> bool {
+    match (Number::from_value(l), Number::from_value(r)) {
+        (Some(Number::Int(x)), Some(Number::Int(y))) => x == y,
+        (Some(a), Some(b)) => a.as_f64() == b.as_f64(),
+        _ => l == r,
+    }
+}
+
+/// 🔍 크기 비교 (< <= > >=)
+/// - 숫자끼리, 문자열끼리만 비교 가능
+/// - null 이 섞이면 결과는 null (조건에서는 거짓으로 취급)
+fn compare(op: BinaryOp, l: &Value, r: &Value) -> Result<Value, String> {
+    if l.is_null() || r.is_null() {
+        return Ok(Value::Null);
+    }
+
+    let ordering = match (l, r) {
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => match (Number::from_value(l), Number::from_value(r)) {
+            (Some(Number::Int(x)), Some(Number::Int(y))) => Some(x.cmp(&y)),
+            (Some(a), Some(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+            _ => {
+                return Err(format!(
+                    "Cannot compare {} and {} with '{}'",
+                    type_name(l),
+                    type_name(r),
+                    binary_symbol(op)
+                ));
             }
-            FieldModifier::Default(_) => {} // 이미 위에서 처리
+        },
+    };
+
+    let Some(ordering) = ordering else {
+        return Ok(Value::Bool(false));
+    };
+
+    let result = match op {
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::Le => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    };
+    Ok(Value::Bool(result))
+}
+
+/// 🔍 조건식에서의 참/거짓 판정
+/// - 거짓: null, false, 0, "", 빈 배열, 빈 객체
+/// - 그 외는 모두 참
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// 🔍 단항 연산 적용
+fn apply_unary(op: UnaryOp, v: &Value) -> Result<Value, String> {
+    match op {
+        UnaryOp::Not => Ok(Value::Bool(!is_truthy(v))),
+        UnaryOp::Neg => match Number::from_value(v) {
+            _ if v.is_null() => Ok(Value::Null),
+            Some(Number::Int(i)) => Ok(i
+                .checked_neg()
+                .map_or_else(|| Value::from(-(i as f64)), Value::from)),
+            Some(Number::Float(f)) => Ok(Value::from(-f)),
+            None => Err(format!("Cannot apply '-' to {}", type_name(v))),
+        },
+    }
+}
+
+fn binary_symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
+
+/// 🔍 에러 메시지용 JSON 타입 이름
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// 🔍 메서드 하나 적용
+/// - prefix / suffix / default 는 결과가 항상 문자열 (null 은 빈 문자열, 빈 값에는 prefix / suffix 를 붙이지 않음)
+/// - 그 밖의 메서드에서 null 은 그대로 null (len 은 0)
+/// - 숫자, bool 등은 문자열 표현으로 바꾼 뒤 적용
+/// - repeat / pad_left 결과가 MAX_METHOD_RESULT_LEN 을 넘으면 에러
+fn apply_method(value: &Value, method: &Method) -> Result<Value, String> {
+    match (method, value) {
+        (Method::Default(default), _) if value.is_null() || value.as_str() == Some("") => {
+            return Ok(Value::from(default.as_str()));
+        }
+        (Method::Prefix(_) | Method::Suffix(_), _) if value.is_null() || value.as_str() == Some("") => {
+            return Ok(Value::from(""));
         }
+        (Method::Len, Value::Null) => return Ok(Value::from(0)),
+        (Method::Len, Value::Array(items)) => return Ok(Value::from(items.len())),
+        (Method::Len, Value::Object(map)) => return Ok(Value::from(map.len())),
+        (_, Value::Null) => return Ok(Value::Null),
+        _ => {}
     }
 
-    Ok(value)
+    let text = value_to_string(value);
+    let result = match method {
+        Method::Len => return Ok(Value::from(text.chars().count())),
+        Method::RegexMatch(pattern) => return Ok(Value::Bool(pattern.0.is_match(&text))),
+        Method::RegexExtract(pattern, group) => {
+            return Ok(pattern
+                .0
+                .captures(&text)
+                .and_then(|captures| captures.get(*group))
+                .map_or(Value::Null, |m| Value::from(m.as_str())));
+        }
+        Method::RegexSplit(pattern) => {
+            return Ok(Value::Array(pattern.0.split(&text).map(Value::from).collect()));
+        }
+        Method::RegexReplace(pattern, to) => pattern.0.replace_all(&text, to.as_str()).into_owned(),
+        Method::Upper => text.to_uppercase(),
+        Method::Lower => text.to_lowercase(),
+        Method::Trim => text.trim().to_string(),
+        Method::TrimStart => text.trim_start().to_string(),
+        Method::Replace(from, _) if from.is_empty() => text,
+        Method::Replace(from, to) => text.replace(from.as_str(), to),
+        Method::Slice(start, end) => slice_chars(&text, *start, *end),
+        Method::PadLeft(width, fill) => {
+            let count = width.saturating_sub(text.chars().count());
+            check_result_len("pad_left", count.checked_mul(fill.len_utf8()).and_then(|n| n.checked_add(text.len())))?;
+            let padding: String = std::iter::repeat_n(*fill, count).collect();
+            padding + &text
+        }
+        Method::Repeat(times) => {
+            check_result_len("repeat", text.len().checked_mul(*times))?;
+            text.repeat(*times)
+        }
+        Method::Prefix(prefix) => format!("{}{}", prefix, text),
+        Method::Suffix(suffix) => format!("{}{}", text, suffix),
+        Method::Default(_) => text,
+    };
+    Ok(Value::String(result))
+}
+
+/// 🔍 결과 문자열 크기 확인 (None 은 usize 범위를 넘은 경우)
+fn check_result_len(method: &str, len: Option<usize>) -> Result<(), String> {
+    match len {
+        Some(len) if len <= MAX_METHOD_RESULT_LEN => Ok(()),
+        _ => Err(format!(
+            "Result of `{}` would be too large (limit {} bytes)",
+            method, MAX_METHOD_RESULT_LEN
+        )),
+    }
+}
+
+/// 🔍 문자 단위 부분 문자열 [start, end) (음수는 끝에서부터, 범위를 벗어나면 잘라냄)
+fn slice_chars(text: &str, start: i64, end: Option<i64>) -> String {
+    let len = text.chars().count() as i64;
+    let resolve = |index: i64| if index < 0 { (len + index).max(0) } else { index.min(len) };
+
+    let start = resolve(start);
+    let end = end.map_or(len, resolve);
+    if start >= end {
+        return String::new();
+    }
+    text.chars().skip(start as usize).take((end - start) as usize).collect()
 }
 
 /// 🔍 중첩 경로 (["a", "b", "c"]) 에 따라 값을 가져옴
-fn get_nested_value_as_string(
-    record: &IndexMap<String, Value>,
+fn get_nested_value<'a>(
+    record: &'a Record,
     path: &[String],
-) -> Option<String> {
+) -> Option<&'a Value> {
     let mut current: &Value = record.get(&path[0])?;
 
     for key in &path[1..] {
@@ -132,8 +440,16 @@ fn get_nested_value_as_string(
         }
     }
 
-    match current {
-        Value::String(s) => Some(s.clone()),
-        other => Some(other.to_string()),
+    Some(current)
+}
+
+/// 🔍 문자열 문맥(연결, 수정자)에서 사용할 문자열 표현
+/// - 문자열은 따옴표 없이 그대로, null 은 빈 문자열
+/// - 그 외(숫자, bool, 배열, 객체)는 JSON 표기
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
     }
 }
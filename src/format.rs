@@ -0,0 +1,151 @@
+//! ✅ format.rs
+//!
+//! 입출력 데이터 형식 (jsonl / json / csv / tsv / parquet)
+//! - input / output 명령에서 직접 지정 (`input csv "x.csv";`), 없으면 확장자로 판단
+//! - 압축 확장자는 건너뛰고 판단 (`data.csv.gz` → csv)
+//! - json (하나의 배열) 은 출력 전용, `json pretty` 는 들여쓰기
+//! - parquet 은 출력 전용, 스키마는 레코드에서 추론하거나 `schema { ... }` 로 선언
+//! - csv / tsv 는 구분자, 헤더, 따옴표 방식, 열 순서를 옵션으로 조정
+
+use crate::compression::Compression;
+
+use std::path::Path;
+
+/// ✅ input / output 에 적을 수 있는 형식 이름 (오타 제안용)
+pub const FORMAT_NAMES: &[&str] = &["jsonl", "ndjson", "json", "csv", "tsv", "parquet"];
+
+/// ✅ 지원하는 데이터 형식
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Jsonl,
+    Json,       // 최상위 배열 하나
+    JsonPretty, // 들여쓰기한 배열
+    Csv,
+    Tsv,
+    Parquet,
+}
+
+impl Format {
+    /// 🔹 이름으로 찾기 (`input csv ...` 등)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "jsonl" | "ndjson" => Some(Format::Jsonl),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "tsv" => Some(Format::Tsv),
+            "parquet" => Some(Format::Parquet),
+            _ => None,
+        }
+    }
+
+    /// 🔹 파일 확장자로 판단 (압축 확장자는 건너뜀, 알 수 없으면 jsonl)
+    pub fn from_extension(path: &str) -> Self {
+        let path = Path::new(path);
+        let path = match Compression::from_extension(&path.to_string_lossy()) {
+            Compression::None => path,
+            _ => Path::new(path.file_stem().unwrap_or_default()),
+        };
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("json") => Format::Json,
+            Some("csv") => Format::Csv,
+            Some("tsv" | "tab") => Format::Tsv,
+            Some("parquet") => Format::Parquet,
+            _ => Format::Jsonl,
+        }
+    }
+
+    /// 🔹 표 형식(csv / tsv) 여부
+    pub fn is_table(self) -> bool {
+        matches!(self, Format::Csv | Format::Tsv)
+    }
+
+    /// 🔹 기본 구분자
+    pub fn default_delimiter(self) -> u8 {
+        match self {
+            Format::Tsv => b'\t',
+            _ => b',',
+        }
+    }
+}
+
+/// ✅ csv / tsv 출력 시 따옴표 처리 방식
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStyle {
+    Necessary,  // 구분자, 따옴표, 줄바꿈이 있을 때만 (기본값)
+    Always,     // 모든 칸
+    NonNumeric, // 숫자가 아닌 칸
+    Never,      // 따옴표 없음
+}
+
+impl QuoteStyle {
+    /// 🔹 이름으로 찾기 (`quote "always"` 등)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "necessary" => Some(QuoteStyle::Necessary),
+            "always" => Some(QuoteStyle::Always),
+            "non_numeric" => Some(QuoteStyle::NonNumeric),
+            "never" => Some(QuoteStyle::Never),
+            _ => None,
+        }
+    }
+}
+
+/// ✅ csv / tsv 옵션 (지정하지 않은 항목은 None → 형식별 기본값)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableOptions {
+    pub header: Option<bool>,         // 첫 줄이 헤더인지 (입력 / 출력 모두 기본값 true)
+    pub delimiter: Option<u8>,        // 구분자 (csv 는 ',', tsv 는 탭)
+    pub quote: Option<QuoteStyle>,    // 출력 전용
+    pub columns: Option<Vec<String>>, // 출력 전용: 열 순서 (점으로 중첩 필드 지정, `meta.score`)
+}
+
+impl TableOptions {
+    /// 🔹 아무 옵션도 지정하지 않았는지 (jsonl 에 csv 옵션을 쓴 경우 확인용)
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// ✅ parquet 열 타입 (`schema { ... }` 선언용, 모든 열은 null 허용)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    String,
+    Int,   // 64비트 정수
+    Float, // 64비트 실수
+    Bool,
+    List(Box<ColumnType>),             // [타입]
+    Struct(Vec<(String, ColumnType)>), // { 이름: 타입, ... }
+}
+
+impl ColumnType {
+    /// 🔹 이름으로 찾기 (`string`, `int`, `float`, `bool`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(ColumnType::String),
+            "int" => Some(ColumnType::Int),
+            "float" => Some(ColumnType::Float),
+            "bool" => Some(ColumnType::Bool),
+            _ => None,
+        }
+    }
+}
+
+/// ✅ parquet 옵션 (지정하지 않은 항목은 None → 기본값)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParquetOptions {
+    pub schema: Option<Vec<(String, ColumnType)>>, // None 이면 첫 row group 의 레코드에서 추론
+    pub row_group_size: Option<usize>,             // row group 당 레코드 수
+}
+
+impl ParquetOptions {
+    /// 🔹 아무 옵션도 지정하지 않았는지 (다른 형식에 parquet 옵션을 쓴 경우 확인용)
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
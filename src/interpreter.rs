@@ -1,141 +1,542 @@
 //! ✅ interpreter.rs
 //!
 //! DSL 명령어(Command)를 받아 실제 동작을 수행하는 인터프리터
-//! - input/output 파일 처리
-//! - print / print line
+//! - input/output 파일 처리 ("-" 는 stdin/stdout, 명령줄 옵션으로 덮어쓰기 가능)
+//! - jsonl / json / csv / tsv / parquet 형식 (지정하지 않으면 확장자로 판단)
+//! - partition by / rotate every 로 출력을 여러 파일로 나눔 (partition.rs)
+//! - 여러 입력 파일 / glob 패턴은 정해진 순서대로 이어서 읽음
+//! - print / print line (스크립트 위치와 관계없이 입력 레코드를 출력)
 //! - transform 명령 실행 및 JSON 변환 처리
+//! - filter / where 명령으로 조건에 맞지 않는 레코드 제거
+//! - on_error 명령으로 잘못된 입력 줄 처리 방식 지정 (abort / skip / quarantine)
+//! - 이름 있는 output 여러 개 + emit to / route 로 레코드를 골라 보냄 (대상별 변환 가능)
+//!
+//! 실행은 스트리밍 파이프라인으로 이루어진다.
+//! - input 에서 한 줄씩 읽은 레코드가 스크립트에 적힌 순서대로
+//!   filter / transform 단계를 통과한 뒤 곧바로 output 에 기록됨
+//! - print / print line N 은 읽은 그대로의 입력 레코드를 출력 (N 은 읽은 순서, filter 와 무관)
+//! - emit / route 단계는 그 시점의 레코드를 이름 있는 output 에 복사해 보내고,
+//!   레코드 자체는 바뀌지 않은 채 다음 단계로 넘어감
+//! - input / output 명령은 위치와 관계없이 파이프라인의 시작과 끝
+//! - 전체 데이터를 메모리에 올리지 않으므로 파일 크기와 관계없이 일정한 메모리 사용
+//!
+//! 상태 메시지(저장 완료, filter 결과 등)는 stdout 을 순수 JSONL 로 유지하기 위해 stderr 로 출력
 
-use crate::parser::Command;
-use crate::evaluator::{evaluate_expression, EvaluatorState};
-
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use crate::diagnostic::Diagnostic;
+use crate::parser::{Command, EmitTarget, ErrorPolicy, Expression, InputSpec, OutputSpec, RouteRule};
+use crate::format::FORMAT_NAMES;
+use crate::modifier::{closest_name, unknown_name};
+use crate::evaluator::{evaluate_expression, is_truthy, value_to_string, EvaluatorState, Record};
+use crate::partition::PartitionedWriter;
+use crate::sink::{is_broken_pipe, print_record, RecordWriter, STDOUT_PATH};
+use crate::source::{expand_input_paths, MalformedLine, ReadError, RecordReader};
+use crate::span::{Span, Spanned};
 
-use serde_json::Value;
 use indexmap::IndexMap;
+use serde_json::Value;
+
+/// ✅ 레코드가 통과하는 파이프라인 단계
+enum Stage {
+    Transform(Vec<(String, Spanned<Expression>)>),
+    Filter {
+        condition: Spanned<Expression>,
+        dropped: usize, // 이 단계에서 제거된 레코드 수
+    },
+    Emit {
+        targets: Vec<EmitTarget>,
+        condition: Option<Spanned<Expression>>,
+    },
+    Route(Vec<RouteRule>), // 처음으로 조건이 맞는 규칙 하나만 적용
+}
+
+/// ✅ 입력 레코드 출력 요청 (print / print line)
+enum Print {
+    All,         // print;
+    Line(usize), // print line N; (N번째로 읽은 레코드, 1부터)
+}
+
+/// ✅ 이름 있는 출력 (emit / route 로 보낸 레코드만 기록)
+struct NamedOutput {
+    spec: OutputSpec,
+    span: Span,
+    writer: Option<PartitionedWriter>, // 스트리밍 직전에 생성
+    records: usize,                    // 기록한 레코드 수
+}
 
 /// ✅ DSL 인터프리터 구조체
 pub struct Interpreter {
-    input_file_path: Option<String>,
-    output_file_path: Option<String>,
-    jsonl_data: Vec<IndexMap<String, Value>>,        // 원본 JSONL
-    transformed_data: Vec<IndexMap<String, Value>>,  // transform 결과
+    input: Option<(InputSpec, Option<Span>)>,        // 명령줄에서 지정한 경우 Span 없음
+    output: Option<(OutputSpec, Option<Span>)>,
+    outputs: IndexMap<String, NamedOutput>,         // 이름 → 이름 있는 출력 (선언 순서)
+    stages: Vec<Stage>,          // input → (stages...) → output
+    prints: Vec<Print>,          // 적힌 순서대로, 레코드를 읽자마자 출력
+    records_read: usize,         // 지금까지 읽은 입력 레코드 수
+    error_policy: Option<Spanned<ErrorPolicy>>,
+    malformed_count: usize,      // skip / quarantine 된 입력 줄 수
+    eval_state: EvaluatorState,
 }
 
 impl Interpreter {
     /// 🔹 Interpreter 인스턴스 생성
     pub fn new() -> Self {
         Self {
-            input_file_path: None,
-            output_file_path: None,
-            jsonl_data: Vec::new(),
-            transformed_data: Vec::new(),
+            input: None,
+            output: None,
+            outputs: IndexMap::new(),
+            stages: Vec::new(),
+            prints: Vec::new(),
+            records_read: 0,
+            error_policy: None,
+            malformed_count: 0,
+            eval_state: EvaluatorState::new(),
         }
     }
 
     /// 🔹 DSL 명령어 실행
-    pub fn run(&mut self, commands: Vec<Command>) -> Result<(), String> {
-        let mut eval_state = EvaluatorState::new();
-
+    ///
+    /// `input_override` / `output_override` 는 명령줄 옵션으로, 스크립트의 input/output 경로보다 우선
+    /// (`output_override` 는 이름 없는 output 에만 적용)
+    pub fn run(
+        &mut self,
+        commands: Vec<Spanned<Command>>,
+        input_override: Option<String>,
+        output_override: Option<String>,
+    ) -> Result<(), Diagnostic> {
         for command in commands {
-            match command {
-                // 📌 input "파일명";
-                Command::Input(path) => {
-                    self.input_file_path = Some(path.clone());
-                    self.jsonl_data = Self::read_jsonl_file(&path)?;
-                }
+            self.add_command(command)?;
+        }
+        self.check_emit_targets()?;
 
-                // 📌 output "파일명";
-                Command::Output(path) => {
-                    self.output_file_path = Some(path.clone());
+        if let Some(path) = input_override {
+            match &mut self.input {
+                Some((spec, span)) => {
+                    spec.paths = vec![path];
+                    *span = None;
                 }
+                None => {
+                    let spec = InputSpec { paths: vec![path], ..InputSpec::default() };
+                    self.input = Some((spec, None));
+                }
+            }
+        }
+        if let Some(path) = output_override {
+            match &mut self.output {
+                Some((spec, span)) => {
+                    spec.path = path;
+                    *span = None;
+                }
+                None => {
+                    let spec = OutputSpec { path, ..OutputSpec::default() };
+                    self.output = Some((spec, None));
+                }
+            }
+        }
 
-                // 📌 print;
-                Command::Print => {
-                    for value in &self.jsonl_data {
-                        println!("{}", serde_json::to_string(value).unwrap());
-                    }
+        self.check_output_paths()?;
+
+        // 🔹 입력 파일 목록 (glob 패턴 확장, 적힌 순서 + 패턴 내 이름순)
+        let (input_paths, input_span) = match &self.input {
+            Some((spec, span)) => {
+                (expand_input_paths(&spec.paths).map_err(|e| Diagnostic::at(e, *span))?, *span)
+            }
+            None => (Vec::new(), None),
+        };
+
+        // 🔹 출력 준비 (나누지 않는 출력이면 입력이 없어도 빈 파일은 생성)
+        let mut writer = match &self.output {
+            Some((spec, span)) => Some(
+                PartitionedWriter::create(spec, &input_paths)
+                    .map_err(|e| Diagnostic::at(e, *span))?,
+            ),
+            None => None,
+        };
+        for output in self.outputs.values_mut() {
+            let writer = PartitionedWriter::create(&output.spec, &input_paths)
+                .map_err(|e| Diagnostic::new(e, output.span))?;
+            output.writer = Some(writer);
+        }
+
+        // 🔹 quarantine 파일 준비
+        let mut quarantine = match &self.error_policy {
+            Some(Spanned { node: ErrorPolicy::Quarantine(path), span }) => Some(
+                RecordWriter::create_jsonl(path, &input_paths)
+                    .map_err(|e| Diagnostic::new(e, *span))?,
+            ),
+            _ => None,
+        };
+
+        // 🔹 레코드 단위 스트리밍
+        // 📌 stdout 이 닫힌 경우(`| head`)는 읽기만 멈추고 나머지 출력은 정상적으로 마무리
+        let streamed = self.stream(&input_paths, input_span, &mut writer, &mut quarantine);
+        if let Err(e) = &streamed
+            && !is_broken_pipe(&e.message)
+        {
+            return streamed;
+        }
+
+        if let Some(writer) = writer {
+            let split = writer.is_split();
+            let span = self.output.as_ref().and_then(|(_, span)| *span);
+            let files = match writer.finish() {
+                Ok(files) => files,
+                Err(e) if is_broken_pipe(&e) => Vec::new(),
+                Err(e) => return Err(Diagnostic::at(e, span)),
+            };
+            match &self.output {
+                Some((spec, _)) if spec.path == STDOUT_PATH => {}
+                Some((spec, _)) if split => {
+                    eprintln!("✅ Output saved to {} file(s) ('{}')", files.len(), spec.path)
                 }
+                Some((spec, _)) => eprintln!("✅ Output saved to '{}'", spec.path),
+                None => {}
+            }
+        }
+        for (name, output) in std::mem::take(&mut self.outputs) {
+            let Some(writer) = output.writer else { continue };
+            let split = writer.is_split();
+            let files = match writer.finish() {
+                Ok(files) => files,
+                Err(e) if is_broken_pipe(&e) => Vec::new(),
+                Err(e) => return Err(Diagnostic::new(e, output.span)),
+            };
+            match &output.spec.path {
+                path if path == STDOUT_PATH => {}
+                path if split => eprintln!(
+                    "✅ Output '{}' saved to {} file(s) ('{}', {} record(s))",
+                    name, files.len(), path, output.records
+                ),
+                path => eprintln!("✅ Output '{}' saved to '{}' ({} record(s))", name, path, output.records),
+            }
+        }
+
+        if let Some(quarantine) = quarantine {
+            quarantine.finish()?;
+        }
 
-                // 📌 print line N;
-                Command::PrintLine(line_num) => {
-                    if line_num == 0 || line_num > self.jsonl_data.len() {
-                        println!("⚠️ Line number {} is out of range.", line_num);
-                    } else {
-                        let item = &self.jsonl_data[line_num - 1];
-                        println!("{}", serde_json::to_string(item).unwrap());
+        streamed?;
+        self.report();
+        Ok(())
+    }
+
+    /// 🔹 입력 파일을 차례로 읽어 레코드마다 파이프라인 실행 후 이름 없는 output 에 기록
+    fn stream(
+        &mut self,
+        input_paths: &[String],
+        input_span: Option<Span>,
+        writer: &mut Option<PartitionedWriter>,
+        quarantine: &mut Option<RecordWriter>,
+    ) -> Result<(), Diagnostic> {
+        let tolerate_malformed = self
+            .error_policy
+            .as_ref()
+            .is_some_and(|policy| policy.node != ErrorPolicy::Abort);
+
+        let input_spec = self.input.as_ref().map(|(spec, _)| spec.clone()).unwrap_or_default();
+        for path in input_paths {
+            let mut reader = RecordReader::open(path, &input_spec)
+                .map_err(|e| Diagnostic::at(e, input_span))?;
+            self.eval_state.source_file = path.clone();
+
+            while let Some(record) = reader.next_record() {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(ReadError::Malformed(line)) if tolerate_malformed => {
+                        self.malformed_count += 1;
+                        if let Some(quarantine) = quarantine {
+                            quarantine.write(&quarantine_record(path, line))?;
+                        }
+                        continue;
                     }
+                    Err(e) => return Err(Diagnostic::at(e.message(path), input_span)),
+                };
+                self.eval_state.source_line = reader.line_number();
+
+                if let Some(record) = self.process(record)?
+                    && let Some(writer) = writer
+                    && let Some((spec, span)) = &self.output
+                {
+                    write_record(writer, spec, *span, &record, &mut self.eval_state)?;
                 }
+            }
+        }
+        Ok(())
+    }
 
-                // 📌 transform { ... }
-                Command::Transform(assignments) => {
-                    self.transformed_data.clear();
+    /// 🔹 명령어 하나를 파이프라인에 추가
+    fn add_command(&mut self, command: Spanned<Command>) -> Result<(), Diagnostic> {
+        let Spanned { node, span } = command;
 
-                    for original in &self.jsonl_data {
-                        let mut new_record = IndexMap::new();
+        match node {
+            // 📌 input "파일명";
+            Command::Input(spec) => {
+                if self.input.is_some() {
+                    return Err(Diagnostic::new("Only one 'input' command is allowed", span));
+                }
+                self.input = Some((spec, Some(span)));
+            }
 
-                        for (field_name, expr) in &assignments {
-                            let value = evaluate_expression(expr, original, &mut eval_state)?;
-                            new_record.insert(field_name.clone(), value);
-                        }
+            // 📌 output 이름 "파일명"; → emit / route 대상
+            Command::Output(spec) if spec.name.is_some() => {
+                let name = spec.name.clone().unwrap_or_default();
+                if self.outputs.contains_key(&name) {
+                    return Err(Diagnostic::new(format!("Output '{}' is already defined", name), span));
+                }
+                self.outputs.insert(name, NamedOutput { spec, span, writer: None, records: 0 });
+            }
 
-                        self.transformed_data.push(new_record);
-                    }
+            // 📌 output "파일명";
+            Command::Output(spec) => {
+                if self.output.is_some() {
+                    return Err(Diagnostic::new("Only one 'output' command is allowed", span));
+                }
+                self.output = Some((spec, Some(span)));
+            }
+
+            // 📌 on_error abort|skip|quarantine "파일명";
+            Command::OnError(policy) => {
+                if self.error_policy.is_some() {
+                    return Err(Diagnostic::new("Only one 'on_error' command is allowed", span));
                 }
+                self.error_policy = Some(Spanned::new(policy, span));
             }
+
+            // 📌 print; → 모든 입력 레코드 출력
+            Command::Print => self.prints.push(Print::All),
+
+            // 📌 print line N; → N번째 입력 레코드 출력
+            Command::PrintLine(line) => self.prints.push(Print::Line(line)),
+
+            // 📌 transform { ... }
+            Command::Transform(assignments) => self.stages.push(Stage::Transform(assignments)),
+
+            // 📌 filter <조건>; / where <조건>;
+            Command::Filter(condition) => {
+                self.stages.push(Stage::Filter { condition, dropped: 0 })
+            }
+
+            // 📌 emit to 이름, ... [when <조건>];
+            Command::Emit { targets, condition } => self.stages.push(Stage::Emit { targets, condition }),
+
+            // 📌 route { <조건> => 이름, ...; else => 이름; }
+            Command::Route(rules) => self.stages.push(Stage::Route(rules)),
         }
 
-        // 🔹 결과 저장
-        if let Some(path) = &self.output_file_path {
-            let data = if !self.transformed_data.is_empty() {
-                &self.transformed_data
-            } else {
-                &self.jsonl_data
-            };
+        Ok(())
+    }
+
+    /// 🔹 emit / route 대상이 모두 선언된 output 인지 확인 (output 은 스크립트 어디에 있어도 됨)
+    ///
+    /// emit / route 가 한 번도 가리키지 않는 이름 있는 output 도 에러
+    /// (`output jsnol "x.json";` 처럼 형식 이름 오타가 출력 이름으로 읽힌 경우 형식 이름 제안)
+    fn check_emit_targets(&self) -> Result<(), Diagnostic> {
+        let targets: Vec<&EmitTarget> = self
+            .stages
+            .iter()
+            .flat_map(|stage| match stage {
+                Stage::Emit { targets, .. } => targets.iter().collect::<Vec<_>>(),
+                Stage::Route(rules) => rules.iter().flat_map(|rule| &rule.targets).collect(),
+                _ => Vec::new(),
+            })
+            .collect();
 
-            Self::save_to_output_file(path, data)?;
+        for target in &targets {
+            if !self.outputs.contains_key(&target.output.node) {
+                return Err(Diagnostic::new(
+                    format!("Unknown output '{}' (declare it with 'output {} \"file\";')", target.output.node, target.output.node),
+                    target.output.span,
+                ));
+            }
+        }
+        for (name, output) in &self.outputs {
+            if targets.iter().any(|target| &target.output.node == name) {
+                continue;
+            }
+            let message = match closest_name(name, FORMAT_NAMES) {
+                Some(format) => unknown_name("format", name, &[format]),
+                None => format!("Output '{}' never receives records (send them with 'emit to {};' or 'route')", name, name),
+            };
+            return Err(Diagnostic::new(message, output.span));
         }
+        Ok(())
+    }
+
+    /// 🔹 두 출력(output, 이름 있는 output, quarantine)이 같은 파일에 쓰지 않는지 확인
+    ///
+    /// 같은 경로면 임시 파일을 함께 쓰다가 교체 단계에서 실패하므로 시작 전에 에러 (stdout 은 예외)
+    fn check_output_paths(&self) -> Result<(), Diagnostic> {
+        let unnamed = self.output.iter().map(|(spec, span)| (spec.path.as_str(), *span));
+        let named = self.outputs.values().map(|output| (output.spec.path.as_str(), Some(output.span)));
+        let quarantine = self.error_policy.iter().filter_map(|policy| match &policy.node {
+            ErrorPolicy::Quarantine(path) => Some((path.as_str(), Some(policy.span))),
+            _ => None,
+        });
 
+        let mut seen: Vec<&str> = Vec::new();
+        for (path, span) in unnamed.chain(named).chain(quarantine) {
+            if path == STDOUT_PATH {
+                continue;
+            }
+            if seen.contains(&path) {
+                return Err(Diagnostic::at(format!("Another output already writes to '{}'", path), span));
+            }
+            seen.push(path);
+        }
         Ok(())
     }
 
-    /// 🔹 JSONL 파일 읽기
-    fn read_jsonl_file(path: &str) -> Result<Vec<IndexMap<String, Value>>, String> {
-        let file = File::open(path)
-            .map_err(|e| format!("Failed to open file '{}': {}", path, e))?;
-        let reader = BufReader::new(file);
+    /// 🔹 레코드 하나를 모든 단계에 통과시킴 (filter 로 제거되면 None)
+    fn process(&mut self, mut record: Record) -> Result<Option<Record>, Diagnostic> {
+        self.records_read += 1;
+        for print in &self.prints {
+            let matches = match print {
+                Print::All => true,
+                Print::Line(line) => *line == self.records_read,
+            };
+            if matches {
+                print_record(&record).map_err(|e| Diagnostic::at(e, None))?;
+            }
+        }
+
+        for stage in &mut self.stages {
+            match stage {
+                Stage::Transform(assignments) => {
+                    record = apply_transform(assignments, &record, &mut self.eval_state)?;
+                }
+
+                Stage::Filter { condition, dropped } => {
+                    let value = evaluate_expression(condition, &record, &mut self.eval_state)?;
+                    if !is_truthy(&value) {
+                        *dropped += 1;
+                        return Ok(None);
+                    }
+                }
+
+                Stage::Emit { targets, condition } => {
+                    if let Some(condition) = condition
+                        && !is_truthy(&evaluate_expression(condition, &record, &mut self.eval_state)?)
+                    {
+                        continue;
+                    }
+                    for target in targets.iter() {
+                        emit(target, &record, &mut self.outputs, &mut self.eval_state)?;
+                    }
+                }
+
+                Stage::Route(rules) => {
+                    for rule in rules.iter() {
+                        if let Some(condition) = &rule.condition
+                            && !is_truthy(&evaluate_expression(condition, &record, &mut self.eval_state)?)
+                        {
+                            continue;
+                        }
+                        for target in &rule.targets {
+                            emit(target, &record, &mut self.outputs, &mut self.eval_state)?;
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(Some(record))
+    }
+
+    /// 🔹 실행 후 보고 (범위를 벗어난 print line, filter 결과)
+    fn report(&self) {
+        let mut has_filter = false;
+        let mut dropped_total = 0;
+
+        for print in &self.prints {
+            if let Print::Line(line) = print
+                && (*line == 0 || *line > self.records_read)
+            {
+                eprintln!("⚠️ Line number {} is out of range.", line);
+            }
+        }
+
+        for stage in &self.stages {
+            if let Stage::Filter { dropped, .. } = stage {
+                has_filter = true;
+                dropped_total += dropped;
+            }
+        }
+
+        if has_filter {
+            eprintln!("🔹 Filter dropped {} record(s)", dropped_total);
+        }
 
-        let mut result = Vec::new();
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-            let json_map: IndexMap<String, Value> = serde_json::from_str(&line)
-                .map_err(|e| format!("JSON parsing error: {}", e))?;
-            result.push(json_map);
+        if self.malformed_count > 0 {
+            match self.error_policy.as_ref().map(|p| &p.node) {
+                Some(ErrorPolicy::Quarantine(path)) => eprintln!(
+                    "⚠️ {} malformed line(s) quarantined to '{}'",
+                    self.malformed_count, path
+                ),
+                _ => eprintln!("⚠️ Skipped {} malformed line(s)", self.malformed_count),
+            }
         }
+    }
+}
 
-        Ok(result)
+/// 🔧 transform 블록 적용 (적힌 필드만으로 새 레코드 구성)
+fn apply_transform(
+    assignments: &[(String, Spanned<Expression>)],
+    record: &Record,
+    state: &mut EvaluatorState,
+) -> Result<Record, Diagnostic> {
+    let mut new_record = IndexMap::with_capacity(assignments.len());
+
+    for (field_name, expr) in assignments {
+        let value = evaluate_expression(expr, record, state)?;
+        new_record.insert(field_name.clone(), value);
     }
 
-    /// 🔹 결과 JSONL 저장
-    fn save_to_output_file(
-        path: &str,
-        data: &Vec<IndexMap<String, Value>>,
-    ) -> Result<(), String> {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .map_err(|e| format!("Failed to open output file '{}': {}", path, e))?;
-
-        for record in data {
-            let line = serde_json::to_string(record)
-                .map_err(|e| format!("Failed to serialize record: {}", e))?;
-            writeln!(file, "{}", line)
-                .map_err(|e| format!("Failed to write to output file: {}", e))?;
-        }
-
-        println!("✅ Output saved to '{}'", path);
-        Ok(())
+    Ok(new_record)
+}
+
+/// 🔧 출력에 레코드 기록 (partition by 식이 있으면 평가해 파일 선택)
+fn write_record(
+    writer: &mut PartitionedWriter,
+    spec: &OutputSpec,
+    span: Option<Span>,
+    record: &Record,
+    state: &mut EvaluatorState,
+) -> Result<(), Diagnostic> {
+    let key = match &spec.partition {
+        Some(expr) => Some(value_to_string(&evaluate_expression(expr, record, state)?)),
+        None => None,
+    };
+    writer.write(key.as_deref(), record).map_err(|e| Diagnostic::at(e, span))
+}
+
+/// 🔧 emit / route 대상 하나에 레코드 보내기 (대상별 변환이 있으면 적용한 사본)
+fn emit(
+    target: &EmitTarget,
+    record: &Record,
+    outputs: &mut IndexMap<String, NamedOutput>,
+    state: &mut EvaluatorState,
+) -> Result<(), Diagnostic> {
+    let transformed = match &target.transform {
+        Some(assignments) => Some(apply_transform(assignments, record, state)?),
+        None => None,
+    };
+    let record = transformed.as_ref().unwrap_or(record);
+
+    // 대상 이름은 스트리밍 전에 check_emit_targets 로 확인됨
+    let Some(output) = outputs.get_mut(&target.output.node) else { return Ok(()) };
+    if let Some(writer) = &mut output.writer {
+        write_record(writer, &output.spec, Some(output.span), record, state)?;
+        output.records += 1;
     }
+    Ok(())
+}
+
+/// 🔧 quarantine 파일에 기록할 레코드 (원본 줄 + 에러 사유)
+fn quarantine_record(path: &str, line: MalformedLine) -> Record {
+    let mut record = Record::new();
+    record.insert("file".to_string(), Value::from(path));
+    record.insert("line".to_string(), Value::from(line.line_number));
+    record.insert("offset".to_string(), Value::from(line.offset));
+    record.insert("error".to_string(), Value::from(line.reason));
+    record.insert("raw".to_string(), Value::from(line.raw));
+    record
 }
@@ -1,11 +1,18 @@
 //! ✅ DSL용 Lexer
 //!
 //! 이 모듈은 사용자 정의 DSL 스크립트를 의미 있는 Token으로 분해하는 역할을 한다.
-//! - 예: input, output, transform, print 등의 키워드
+//! - 예: input, output, transform, print, filter(where), emit, route 등의 키워드
 //! - 문자열, 필드(@key), 연산자, 중괄호, 함수 호출 등 처리
+//! - 모든 토큰은 Span(바이트 오프셋, 줄, 열)과 함께 반환
+//! - 주석은 토큰으로 만들지 않고 Trivia 로 따로 보관
+//!     - `// ...`  : 줄 끝까지 주석
+//!     - `/* ... */` : 블록 주석 (중첩 가능: `/* a /* b */ c */` 전체가 하나의 주석)
+
+use crate::diagnostic::Diagnostic;
+use crate::span::{Span, Spanned};
 
 use std::iter::Peekable;
-use std::str::Chars;
+use std::str::CharIndices;
 
 /// ✅ DSL에서 사용할 토큰 정의
 #[derive(Debug, Clone, PartialEq)]
@@ -21,55 +28,399 @@ pub enum Token {
     Identifier(String),      // 예: suffix, line
     Field(String),           // 예: @문제
     Number(usize),           // 예: 42
+    Float(f64),              // 예: 3.14
 
     // 🔹 연산자 및 구분자
     Plus,                    // +
+    Minus,                   // -
+    Star,                    // *
+    Slash,                   // /
+    Percent,                 // %
+    EqualEqual, NotEqual,    // ==, !=
+    Less, LessEqual,         // <, <=
+    Greater, GreaterEqual,   // >, >=
+    AndAnd, OrOr,            // &&, ||
+    Bang,                    // !
+    Question, Colon,         // ?, :
     Equal,                   // =
+    FatArrow,                // =>
     Semicolon,              // ;
     LBrace, RBrace,         // {, }
     Dot,                    // .
     LParen, RParen,         // (, )
+    LBracket, RBracket,     // [, ]
+    Comma,                  // ,
 
     // 🔹 예외
     Unknown(char),          // 알 수 없는 문자
-    EOF,                    // 입력 종료
+    Eof,                    // 입력 종료
+}
+
+/// ✅ 토큰 사이에 등장하지만 의미는 없는 요소 (포매터 등에서 보존용)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    LineComment(String),  // `//` 이후 텍스트
+    BlockComment(String), // `/*` 와 `*/` 사이 텍스트
 }
 
 /// ✅ 입력 문자열을 순회하며 Token을 생성하는 구조체
 pub struct Lexer<'a> {
-    input: Peekable<Chars<'a>>,
+    source: &'a str,
+    input: Peekable<CharIndices<'a>>,
+    line: usize,                   // 현재 줄 (1부터)
+    column: usize,                 // 현재 열 (1부터, 문자 단위)
+    trivia: Vec<Spanned<Trivia>>,  // 지금까지 만난 주석들
 }
 
 impl<'a> Lexer<'a> {
     /// 🔹 Lexer 생성자
     pub fn new(source: &'a str) -> Self {
         Self {
-            input: source.chars().peekable(),
+            source,
+            input: source.char_indices().peekable(),
+            line: 1,
+            column: 1,
+            trivia: Vec::new(),
         }
     }
 
-    /// 🔹 문자 하나 읽기 (consume)
+    /// 🔹 지금까지 수집된 주석(Trivia) 목록
+    pub fn trivia(&self) -> &[Spanned<Trivia>] {
+        &self.trivia
+    }
+
+    /// 🔹 문자 하나 읽기 (consume) + 줄/열 갱신
     fn next_char(&mut self) -> Option<char> {
-        self.input.next()
+        let (_, c) = self.input.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
     }
 
     /// 🔹 다음 문자 미리보기 (peek)
     fn peek_char(&mut self) -> Option<&char> {
-        self.input.peek()
+        self.input.peek().map(|(_, c)| c)
+    }
+
+    /// 🔹 다음 문자가 expected 이면 소비하고 true
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek_char() == Some(&expected) {
+            self.next_char();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 🔹 현재 바이트 오프셋
+    fn offset(&mut self) -> usize {
+        self.input.peek().map_or(self.source.len(), |(i, _)| *i)
+    }
+
+    /// 🔹 현재 위치에서 시작하는 빈 Span
+    fn position(&mut self) -> Span {
+        let offset = self.offset();
+        Span {
+            start: offset,
+            end: offset,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// 🔹 start 부터 현재 위치까지의 Span
+    fn span_from(&mut self, start: Span) -> Span {
+        Span {
+            end: self.offset(),
+            ..start
+        }
+    }
+
+    /// 🔹 현재 위치의 남은 입력이 pattern 으로 시작하는지 확인
+    fn starts_with(&mut self, pattern: &str) -> bool {
+        let offset = self.offset();
+        self.source[offset..].starts_with(pattern)
+    }
+
+    /// 🔹 공백과 주석 건너뛰기 (주석은 trivia 에 기록)
+    fn skip_trivia(&mut self) -> Result<(), Diagnostic> {
+        loop {
+            if self.peek_char().is_some_and(|c| c.is_whitespace()) {
+                self.next_char();
+            } else if self.starts_with("//") {
+                self.read_line_comment();
+            } else if self.starts_with("/*") {
+                self.read_block_comment()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 🔹 `// ...` 줄 주석 읽기 (개행 문자는 포함하지 않음)
+    fn read_line_comment(&mut self) {
+        let start = self.position();
+        self.next_char();
+        self.next_char();
+
+        let mut text = String::new();
+        while let Some(&c) = self.peek_char() {
+            if c == '\n' {
+                break;
+            }
+            text.push(c);
+            self.next_char();
+        }
+
+        let text = text.strip_suffix('\r').map(str::to_string).unwrap_or(text);
+        let span = self.span_from(start);
+        self.trivia.push(Spanned::new(Trivia::LineComment(text), span));
+    }
+
+    /// 🔹 `/* ... */` 블록 주석 읽기 (중첩 허용)
+    fn read_block_comment(&mut self) -> Result<(), Diagnostic> {
+        let start = self.position();
+        self.next_char();
+        self.next_char();
+
+        let mut text = String::new();
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.starts_with("/*") {
+                depth += 1;
+            } else if self.starts_with("*/") {
+                depth -= 1;
+            }
+
+            if depth == 0 {
+                self.next_char();
+                self.next_char();
+                break;
+            }
+
+            match self.next_char() {
+                Some(c) => text.push(c),
+                None => {
+                    return Err(Diagnostic::new(
+                        "Unterminated block comment",
+                        self.span_from(start),
+                    ));
+                }
+            }
+        }
+
+        let span = self.span_from(start);
+        self.trivia.push(Spanned::new(Trivia::BlockComment(text), span));
+        Ok(())
     }
 
     /// 🔹 문자열 리터럴 파싱 (예: "...")
-    fn read_string(&mut self) -> Token {
+    ///
+    /// 이스케이프 시퀀스는 여기서 바로 실제 문자로 변환한다.
+    /// - `\"`, `\\`, `\n`, `\t`, `\r`
+    /// - `\u{AC00}` (1~6자리 16진수), `\uXXXX` (4자리, 서로게이트 쌍 허용)
+    ///
+    /// 알 수 없는 이스케이프나 닫히지 않은 문자열은 위치를 포함한 에러로 보고
+    fn read_string(&mut self, start: Span) -> Result<Token, Diagnostic> {
         let mut result = String::new();
 
-        while let Some(c) = self.next_char() {
-            if c == '"' {
+        loop {
+            let escape_start = self.position();
+            match self.next_char() {
+                Some('"') => break,
+                Some('\\') => result.push(self.read_escape(escape_start)?),
+                Some(c) => result.push(c),
+                None => {
+                    return Err(Diagnostic::new(
+                        "Unterminated string literal",
+                        self.span_from(start),
+                    ));
+                }
+            }
+        }
+
+        Ok(Token::StringLiteral(result))
+    }
+
+    /// 🔹 `\` 다음의 이스케이프 시퀀스 하나를 문자로 변환
+    fn read_escape(&mut self, start: Span) -> Result<char, Diagnostic> {
+        match self.next_char() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('u') => self.read_unicode_escape(start),
+            Some(other) => Err(Diagnostic::new(
+                format!("Unknown escape sequence '\\{}'", other),
+                self.span_from(start),
+            )),
+            None => Err(Diagnostic::new(
+                "Unterminated escape sequence",
+                self.span_from(start),
+            )),
+        }
+    }
+
+    /// 🔹 `\u{...}` 또는 `\uXXXX` 유니코드 이스케이프 처리 (`\u` 는 이미 소비됨)
+    fn read_unicode_escape(&mut self, start: Span) -> Result<char, Diagnostic> {
+        let code = if self.peek_char() == Some(&'{') {
+            self.next_char();
+            let mut digits = String::new();
+            loop {
+                match self.next_char() {
+                    Some('}') => break,
+                    Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => digits.push(c),
+                    _ => {
+                        return Err(Diagnostic::new(
+                            "Invalid unicode escape, expected '\\u{XXXX}' with 1-6 hex digits",
+                            self.span_from(start),
+                        ));
+                    }
+                }
+            }
+            if digits.is_empty() {
+                return Err(Diagnostic::new(
+                    "Empty unicode escape '\\u{}'",
+                    self.span_from(start),
+                ));
+            }
+            u32::from_str_radix(&digits, 16).unwrap_or(u32::MAX)
+        } else {
+            let high = self.read_hex4(start)?;
+
+            // UTF-16 서로게이트 쌍: \uD83D\uDE00 → 😀
+            if (0xD800..0xDC00).contains(&high) {
+                if !self.starts_with("\\u") {
+                    return Err(Diagnostic::new(
+                        "Unpaired surrogate in unicode escape",
+                        self.span_from(start),
+                    ));
+                }
+                self.next_char();
+                self.next_char();
+                let low = self.read_hex4(start)?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(Diagnostic::new(
+                        "Invalid low surrogate in unicode escape",
+                        self.span_from(start),
+                    ));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            } else {
+                high
+            }
+        };
+
+        char::from_u32(code).ok_or_else(|| {
+            Diagnostic::new(
+                format!("Invalid unicode code point U+{:X}", code),
+                self.span_from(start),
+            )
+        })
+    }
+
+    /// 🔹 16진수 4자리 읽기 (`\uXXXX` 용)
+    fn read_hex4(&mut self, start: Span) -> Result<u32, Diagnostic> {
+        let mut digits = String::new();
+        for _ in 0..4 {
+            match self.peek_char() {
+                Some(&c) if c.is_ascii_hexdigit() => {
+                    digits.push(c);
+                    self.next_char();
+                }
+                _ => {
+                    return Err(Diagnostic::new(
+                        "Invalid unicode escape, expected '\\uXXXX' with 4 hex digits",
+                        self.span_from(start),
+                    ));
+                }
+            }
+        }
+        Ok(u32::from_str_radix(&digits, 16).unwrap_or(u32::MAX))
+    }
+
+    /// 🔹 `"""..."""` 여러 줄 문자열 파싱 (여는 `"""` 는 이미 소비됨)
+    ///
+    /// - 이스케이프 시퀀스는 일반 문자열과 동일하게 처리
+    /// - 여는 `"""` 바로 뒤가 개행이면 들여쓰기 제거 모드:
+    ///     - 첫 개행과, 닫는 `"""` 만 있는 마지막 줄은 제거
+    ///     - 공백이 아닌 줄들의 공통 들여쓰기(공백/탭)를 모든 줄에서 제거
+    /// - 그 외에는 내용을 그대로 사용
+    fn read_multiline_string(&mut self, start: Span) -> Result<Token, Diagnostic> {
+        // (문자, 이스케이프로 만들어졌는지) → 들여쓰기 계산 시 이스케이프 문자는 제외
+        let mut chars: Vec<(char, bool)> = Vec::new();
+
+        loop {
+            if self.starts_with("\"\"\"") {
+                self.next_char();
+                self.next_char();
+                self.next_char();
                 break;
             }
-            result.push(c);
+
+            let escape_start = self.position();
+            match self.next_char() {
+                Some('\\') => chars.push((self.read_escape(escape_start)?, true)),
+                Some(c) => chars.push((c, false)),
+                None => {
+                    return Err(Diagnostic::new(
+                        "Unterminated multi-line string literal",
+                        self.span_from(start),
+                    ));
+                }
+            }
+        }
+
+        Ok(Token::StringLiteral(dedent(chars)))
+    }
+
+    /// 🔹 현재 위치가 raw 문자열 시작(`r"` 또는 `r#..."`)인지 확인 (`r` 은 이미 소비됨)
+    fn is_raw_string_start(&mut self) -> bool {
+        let offset = self.offset();
+        self.source[offset..].trim_start_matches('#').starts_with('"')
+    }
+
+    /// 🔹 raw 문자열 파싱: `r"..."`, `r#"..."#`, `r##"..."##` ...
+    ///
+    /// 이스케이프를 처리하지 않으며, 같은 개수의 `#` 가 붙은 `"` 가 나올 때까지 읽는다.
+    /// 여러 줄에 걸쳐 작성 가능
+    fn read_raw_string(&mut self, start: Span) -> Result<Token, Diagnostic> {
+        let mut hashes = 0;
+        while self.peek_char() == Some(&'#') {
+            self.next_char();
+            hashes += 1;
+        }
+        self.next_char(); // 여는 "
+
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let mut result = String::new();
+
+        loop {
+            if self.starts_with(&terminator) {
+                for _ in 0..terminator.len() {
+                    self.next_char();
+                }
+                break;
+            }
+
+            match self.next_char() {
+                Some(c) => result.push(c),
+                None => {
+                    return Err(Diagnostic::new(
+                        format!("Unterminated raw string literal, expected closing '{}'", terminator),
+                        self.span_from(start),
+                    ));
+                }
+            }
         }
 
-        Token::StringLiteral(result)
+        Ok(Token::StringLiteral(result))
     }
 
     /// 🔹 @필드 처리 (예: @문제)
@@ -88,7 +439,9 @@ impl<'a> Lexer<'a> {
     }
 
     /// 🔹 식별자 / 숫자 / 키워드 파싱
-    fn read_identifier_or_number(&mut self, first_char: char) -> Token {
+    ///
+    /// 숫자로 시작하는데 숫자가 아닌 문자가 섞였거나 (`12abc`) 정수 범위를 넘으면 위치를 포함한 에러로 보고
+    fn read_identifier_or_number(&mut self, start: Span, first_char: char) -> Result<Token, Diagnostic> {
         let mut value = String::new();
         value.push(first_char);
 
@@ -100,56 +453,158 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        match value.as_str() {
+        // 소수점 숫자 (예: 3.14) → 숫자 뒤에 '.' + 숫자가 이어질 때만
+        if value.chars().all(|c| c.is_ascii_digit()) && self.is_fraction_start() {
+            value.push(self.next_char().unwrap());
+            while let Some(&c) = self.peek_char() {
+                if !c.is_ascii_digit() {
+                    break;
+                }
+                value.push(self.next_char().unwrap());
+            }
+            return Ok(Token::Float(value.parse().unwrap_or(f64::NAN)));
+        }
+
+        if first_char.is_ascii_digit() {
+            return match value.parse::<usize>() {
+                Ok(num) => Ok(Token::Number(num)),
+                Err(_) if value.chars().all(|c| c.is_ascii_digit()) => Err(Diagnostic::new(
+                    format!("Integer literal `{}` is out of range (max {})", value, usize::MAX),
+                    self.span_from(start),
+                )),
+                Err(_) => Err(Diagnostic::new(format!("Invalid number `{}`", value), self.span_from(start))),
+            };
+        }
+
+        Ok(match value.as_str() {
             "input" => Token::Input,
             "output" => Token::Output,
             "transform" => Token::Transform,
             "print" => Token::Print,
-            _ => {
-                if let Ok(num) = value.parse::<usize>() {
-                    Token::Number(num)
-                } else {
-                    Token::Identifier(value)
-                }
-            }
-        }
+            _ => Token::Identifier(value),
+        })
     }
 
-    /// 🔹 입력에서 토큰 하나 파싱
-    pub fn next_token(&mut self) -> Token {
-        while let Some(c) = self.next_char() {
-            match c {
-                '"' => return self.read_string(),
-                '@' => return self.read_field(),
-                '+' => return Token::Plus,
-                '=' => return Token::Equal,
-                ';' => return Token::Semicolon,
-                '{' => return Token::LBrace,
-                '}' => return Token::RBrace,
-                '.' => return Token::Dot,
-                '(' => return Token::LParen,
-                ')' => return Token::RParen,
-                c if c.is_whitespace() => continue,
-                c if c.is_alphanumeric() => return self.read_identifier_or_number(c),
-                other => return Token::Unknown(other),
-            }
-        }
+    /// 🔹 현재 위치가 소수부(`.` + 숫자)로 시작하는지 확인
+    fn is_fraction_start(&mut self) -> bool {
+        let offset = self.offset();
+        let mut rest = self.source[offset..].chars();
+        rest.next() == Some('.') && rest.next().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    /// 🔹 입력에서 토큰 하나 파싱 (위치 포함)
+    pub fn next_token(&mut self) -> Result<Spanned<Token>, Diagnostic> {
+        self.skip_trivia()?;
+        let start = self.position();
 
-        Token::EOF
+        let token = match self.next_char() {
+            None => Token::Eof,
+            Some(c) => match c {
+                '"' if self.starts_with("\"\"") => {
+                    self.next_char();
+                    self.next_char();
+                    self.read_multiline_string(start)?
+                }
+                '"' => self.read_string(start)?,
+                'r' if self.is_raw_string_start() => self.read_raw_string(start)?,
+                '@' => self.read_field(),
+                '+' => Token::Plus,
+                '-' => Token::Minus,
+                '*' => Token::Star,
+                '/' => Token::Slash,
+                '%' => Token::Percent,
+                '=' if self.eat('=') => Token::EqualEqual,
+                '=' if self.eat('>') => Token::FatArrow,
+                '=' => Token::Equal,
+                '!' if self.eat('=') => Token::NotEqual,
+                '!' => Token::Bang,
+                '<' if self.eat('=') => Token::LessEqual,
+                '<' => Token::Less,
+                '>' if self.eat('=') => Token::GreaterEqual,
+                '>' => Token::Greater,
+                '&' if self.eat('&') => Token::AndAnd,
+                '|' if self.eat('|') => Token::OrOr,
+                '?' => Token::Question,
+                ':' => Token::Colon,
+                ';' => Token::Semicolon,
+                '{' => Token::LBrace,
+                '}' => Token::RBrace,
+                '.' => Token::Dot,
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '[' => Token::LBracket,
+                ']' => Token::RBracket,
+                ',' => Token::Comma,
+                c if c.is_alphanumeric() => self.read_identifier_or_number(start, c)?,
+                other => Token::Unknown(other),
+            },
+        };
+
+        Ok(Spanned::new(token, self.span_from(start)))
     }
 
-    /// 🔹 전체 입력을 토큰 리스트로 변환
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    /// 🔹 전체 입력을 토큰 리스트로 변환 (마지막은 항상 Eof)
+    pub fn tokenize(&mut self) -> Result<Vec<Spanned<Token>>, Diagnostic> {
         let mut tokens = Vec::new();
 
         loop {
-            let token = self.next_token();
-            if token == Token::EOF {
+            let token = self.next_token()?;
+            let is_eof = token.node == Token::Eof;
+            tokens.push(token);
+            if is_eof {
                 break;
             }
-            tokens.push(token);
         }
 
-        tokens
+        Ok(tokens)
     }
 }
+
+/// 🔧 여러 줄 문자열의 들여쓰기 제거 (read_multiline_string 참고)
+fn dedent(chars: Vec<(char, bool)>) -> String {
+    // 이스케이프되지 않은 개행 기준으로 줄 분리 (CRLF 의 \r 은 제거)
+    let mut lines: Vec<Vec<(char, bool)>> = vec![Vec::new()];
+    for (c, escaped) in chars {
+        if c == '\n' && !escaped {
+            if let Some(last) = lines.last_mut()
+                && last.last() == Some(&('\r', false))
+            {
+                last.pop();
+            }
+            lines.push(Vec::new());
+        } else if let Some(last) = lines.last_mut() {
+            last.push((c, escaped));
+        }
+    }
+
+    let is_blank = |line: &[(char, bool)]| line.iter().all(|&(c, e)| !e && (c == ' ' || c == '\t'));
+
+    // 여는 """ 뒤가 바로 개행이 아니면 그대로 사용
+    if lines.len() < 2 || !lines[0].is_empty() {
+        let text: Vec<String> = lines
+            .into_iter()
+            .map(|line| line.into_iter().map(|(c, _)| c).collect())
+            .collect();
+        return text.join("\n");
+    }
+
+    lines.remove(0);
+    let closing_indent = match lines.last() {
+        Some(last) if is_blank(last) => lines.pop().map(|l| l.len()),
+        _ => None,
+    };
+
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| line.iter().take_while(|&&(c, e)| !e && (c == ' ' || c == '\t')).count())
+        .chain(closing_indent)
+        .min()
+        .unwrap_or(0);
+
+    let text: Vec<String> = lines
+        .into_iter()
+        .map(|line| line.into_iter().skip(indent).map(|(c, _)| c).collect())
+        .collect();
+    text.join("\n")
+}
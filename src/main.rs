@@ -3,12 +3,25 @@
 //! DSL 실행기의 진입점 (Command Line Interface)
 //! 사용 예시:
 //!     $ mydsl script.jdl
+//!     $ zcat a.jsonl.gz | mydsl script.jdl -i - -o - | head
+//!
+//! 옵션:
+//!     -i, --input <path>   스크립트의 input 경로 대신 사용 ("-" 는 stdin)
+//!     -o, --output <path>  스크립트의 output 경로 대신 사용 ("-" 는 stdout)
 
 mod lexer;
 mod parser;
 mod evaluator;
 mod interpreter;
-mod utils;
+mod source;
+mod sink;
+mod partition;
+mod compression;
+mod columnar;
+mod format;
+mod modifier;
+mod span;
+mod diagnostic;
 
 use lexer::Lexer;
 use parser::Parser;
@@ -20,15 +33,46 @@ use std::fs;
 /// ✅ 디버그 출력용 전역 플래그
 const DEBUG: bool = false;
 
+const USAGE: &str = "❌ Usage: mydsl <script.jdl> [-i|--input <path>] [-o|--output <path>]";
+
+/// ✅ 명령줄 인자
+struct CliArgs {
+    script_path: String,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+/// 🔹 명령줄 인자 파싱 (잘못된 경우 None)
+fn parse_args(args: impl Iterator<Item = String>) -> Option<CliArgs> {
+    let mut script_path = None;
+    let mut input = None;
+    let mut output = None;
+
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => input = Some(args.next()?),
+            "-o" | "--output" => output = Some(args.next()?),
+            _ if script_path.is_none() => script_path = Some(arg),
+            _ => return None,
+        }
+    }
+
+    Some(CliArgs {
+        script_path: script_path?,
+        input,
+        output,
+    })
+}
+
 fn main() {
-    // 🔹 명령줄 인자 확인: mydsl <파일명>
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("❌ Usage: mydsl <script.jdl>");
+    // 🔹 명령줄 인자 확인: mydsl <파일명> [옵션]
+    let Some(cli) = parse_args(env::args()) else {
+        eprintln!("{}", USAGE);
         std::process::exit(1);
-    }
+    };
 
-    let source_path = &args[1];
+    let source_path = &cli.script_path;
 
     // 🔹 DSL 파일 읽기
     let source = fs::read_to_string(source_path).unwrap_or_else(|e| {
@@ -44,7 +88,13 @@ fn main() {
 
     // 🔹 렉싱: 소스 → 토큰 리스트
     let mut lexer = Lexer::new(&source);
-    let tokens = lexer.tokenize();
+    let tokens = match lexer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("❌ Lexer error: {}", e.render(source_path, &source));
+            std::process::exit(1);
+        }
+    };
 
     if DEBUG {
         println!("🔹 Tokens:");
@@ -52,6 +102,12 @@ fn main() {
             println!("  [{:02}] {:?}", i, token);
         }
         println!();
+
+        println!("🔹 Comments:");
+        for (i, comment) in lexer.trivia().iter().enumerate() {
+            println!("  [{:02}] {:?}", i, comment);
+        }
+        println!();
     }
 
     // 🔹 파싱: 토큰 리스트 → 명령어 리스트
@@ -59,7 +115,7 @@ fn main() {
     let commands = match parser.parse() {
         Ok(cmds) => cmds,
         Err(e) => {
-            eprintln!("❌ Parser error: {}", e);
+            eprintln!("❌ Parser error: {}", e.render(source_path, &source));
             std::process::exit(1);
         }
     };
@@ -78,8 +134,12 @@ fn main() {
     }
 
     let mut interpreter = Interpreter::new();
-    if let Err(e) = interpreter.run(commands) {
-        eprintln!("❌ Runtime error: {}", e);
+    if let Err(e) = interpreter.run(commands, cli.input, cli.output) {
+        // 📌 stdout 을 읽던 쪽이 먼저 닫힌 경우(`mydsl x.jdl | head`)는 에러가 아님
+        if sink::is_broken_pipe(&e.message) {
+            return;
+        }
+        eprintln!("❌ Runtime error: {}", e.render(source_path, &source));
         std::process::exit(1);
     }
 }
@@ -0,0 +1,266 @@
+//! ✅ modifier.rs
+//!
+//! 수정자(`.prefix("...")` 등)와 함수(`serial()` 등) 목록
+//! - 수정자마다 이름, 인자 이름과 타입, 필수 인자 수를 한곳에 모아 두고 파싱할 때 검사
+//! - 모르는 이름은 가장 비슷한 이름을 제안 (`Unknown modifier `prefx`, did you mean `prefix`?`)
+//! - 인자 수나 타입이 틀리면 해당 인자 위치와 사용법을 함께 보고
+//! - 검사를 통과한 인자로 Method 생성 (정규식은 여기서 한 번만 컴파일)
+
+use crate::diagnostic::Diagnostic;
+use crate::parser::{Method, Pattern};
+use crate::span::{Span, Spanned};
+
+use regex::Regex;
+use serde_json::Value;
+
+/// ✅ 수정자 인자 타입 (인자는 모두 리터럴)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType {
+    Text,    // 문자열
+    Int,     // 정수 (음수 가능)
+    Count,   // 0 이상의 정수
+    Char,    // 한 글자 문자열
+    Pattern, // 정규식 문자열
+}
+
+impl ArgType {
+    /// 🔹 값이 이 타입에 맞는지
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            ArgType::Text | ArgType::Pattern => value.is_string(),
+            ArgType::Int => value.as_i64().is_some(),
+            ArgType::Count => value.as_u64().is_some(),
+            ArgType::Char => value.as_str().is_some_and(|s| s.chars().count() == 1),
+        }
+    }
+
+    /// 🔹 에러 메시지용 설명
+    fn describe(self) -> &'static str {
+        match self {
+            ArgType::Text => "a string",
+            ArgType::Int => "an integer",
+            ArgType::Count => "a non-negative integer",
+            ArgType::Char => "a single-character string",
+            ArgType::Pattern => "a regular expression string",
+        }
+    }
+}
+
+/// ✅ 수정자 시그니처
+pub struct Signature {
+    pub name: &'static str,
+    pub params: &'static [(&'static str, ArgType)], // (인자 이름, 타입), 필수 인자가 앞쪽
+    pub required: usize,                            // 필수 인자 수 (나머지는 생략 가능)
+    build: fn(&Args) -> Result<Method, Diagnostic>,
+}
+
+impl Signature {
+    /// 🔹 사용법 (`slice(start, [end])`)
+    pub fn usage(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| if i < self.required { name.to_string() } else { format!("[{}]", name) })
+            .collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
+}
+
+/// ✅ 사용할 수 있는 수정자 목록
+pub const MODIFIERS: &[Signature] = &[
+    Signature { name: "prefix", params: &[("text", ArgType::Text)], required: 1, build: |a| Ok(Method::Prefix(a.text(0))) },
+    Signature { name: "suffix", params: &[("text", ArgType::Text)], required: 1, build: |a| Ok(Method::Suffix(a.text(0))) },
+    Signature { name: "default", params: &[("text", ArgType::Text)], required: 1, build: |a| Ok(Method::Default(a.text(0))) },
+    Signature { name: "upper", params: &[], required: 0, build: |_| Ok(Method::Upper) },
+    Signature { name: "lower", params: &[], required: 0, build: |_| Ok(Method::Lower) },
+    Signature { name: "trim", params: &[], required: 0, build: |_| Ok(Method::Trim) },
+    Signature { name: "trim_start", params: &[], required: 0, build: |_| Ok(Method::TrimStart) },
+    Signature {
+        name: "replace",
+        params: &[("from", ArgType::Text), ("to", ArgType::Text)],
+        required: 2,
+        build: |a| Ok(Method::Replace(a.text(0), a.text(1))),
+    },
+    Signature {
+        name: "slice",
+        params: &[("start", ArgType::Int), ("end", ArgType::Int)],
+        required: 1,
+        build: |a| Ok(Method::Slice(a.int(0), a.has(1).then(|| a.int(1)))),
+    },
+    Signature { name: "len", params: &[], required: 0, build: |_| Ok(Method::Len) },
+    Signature {
+        name: "pad_left",
+        params: &[("width", ArgType::Count), ("fill", ArgType::Char)],
+        required: 1,
+        build: |a| Ok(Method::PadLeft(a.count(0), if a.has(1) { a.char(1) } else { ' ' })),
+    },
+    Signature { name: "repeat", params: &[("times", ArgType::Count)], required: 1, build: |a| Ok(Method::Repeat(a.count(0))) },
+    Signature {
+        name: "regex_replace",
+        params: &[("pattern", ArgType::Pattern), ("replacement", ArgType::Text)],
+        required: 2,
+        build: |a| Ok(Method::RegexReplace(a.pattern(0)?, a.text(1))),
+    },
+    Signature {
+        name: "regex_match",
+        params: &[("pattern", ArgType::Pattern)],
+        required: 1,
+        build: |a| Ok(Method::RegexMatch(a.pattern(0)?)),
+    },
+    Signature {
+        name: "regex_extract",
+        params: &[("pattern", ArgType::Pattern), ("group", ArgType::Count)],
+        required: 1,
+        build: build_regex_extract,
+    },
+    Signature {
+        name: "regex_split",
+        params: &[("pattern", ArgType::Pattern)],
+        required: 1,
+        build: |a| Ok(Method::RegexSplit(a.pattern(0)?)),
+    },
+];
+
+/// ✅ 사용할 수 있는 함수 이름 (`raw()`, `serial()` 등, 인자 검사는 파서에서)
+pub const FUNCTIONS: &[&str] = &["raw", "serial", "source_file", "source_line"];
+
+/// ✅ 시그니처 검사를 통과한 인자 (타입이 시그니처와 일치함이 보장됨)
+pub struct Args<'a>(&'a [Spanned<Value>]);
+
+impl Args<'_> {
+    fn has(&self, index: usize) -> bool {
+        index < self.0.len()
+    }
+
+    fn text(&self, index: usize) -> String {
+        self.0[index].node.as_str().unwrap_or_default().to_string()
+    }
+
+    fn int(&self, index: usize) -> i64 {
+        self.0[index].node.as_i64().unwrap_or_default()
+    }
+
+    fn count(&self, index: usize) -> usize {
+        self.0[index].node.as_u64().unwrap_or_default() as usize
+    }
+
+    fn char(&self, index: usize) -> char {
+        self.text(index).chars().next().unwrap_or(' ')
+    }
+
+    /// 🔹 정규식 컴파일 (잘못된 패턴은 인자 위치의 파싱 에러)
+    fn pattern(&self, index: usize) -> Result<Pattern, Diagnostic> {
+        Regex::new(&self.text(index)).map(Pattern).map_err(|e| {
+            // 구문 에러는 패턴과 화살표를 포함한 여러 줄이므로 사유 한 줄만 사용 (위치는 Diagnostic 이 표시)
+            let reason = match &e {
+                regex::Error::Syntax(message) => message
+                    .lines()
+                    .find_map(|line| line.strip_prefix("error: "))
+                    .unwrap_or(message)
+                    .to_string(),
+                other => other.to_string(),
+            };
+            Diagnostic::new(format!("Invalid regular expression: {}", reason), self.0[index].span)
+        })
+    }
+}
+
+/// 🔧 `regex_extract(pattern, [group])` (그룹 번호가 패턴에 있는지 확인)
+fn build_regex_extract(args: &Args) -> Result<Method, Diagnostic> {
+    let pattern = args.pattern(0)?;
+    let group = if args.has(1) { args.count(1) } else { 0 };
+    let groups = pattern.0.captures_len() - 1;
+
+    if group > groups {
+        return Err(Diagnostic::new(
+            format!("Pattern has no group {} (it has {})", group, groups),
+            args.0[1].span,
+        ));
+    }
+    Ok(Method::RegexExtract(pattern, group))
+}
+
+/// 🔧 이름과 인자를 검사해 수정자 생성
+///
+/// `span` 은 `.이름(...)` 전체 (인자 수가 틀렸을 때 표시)
+pub fn build_modifier(
+    name: &Spanned<String>,
+    args: &[Spanned<Value>],
+    span: Span,
+) -> Result<Method, Diagnostic> {
+    let Some(signature) = MODIFIERS.iter().find(|s| s.name == name.node) else {
+        let names: Vec<&str> = MODIFIERS.iter().map(|s| s.name).collect();
+        return Err(Diagnostic::new(unknown_name("modifier", &name.node, &names), name.span));
+    };
+
+    if args.len() < signature.required || args.len() > signature.params.len() {
+        let expected = match (signature.required, signature.params.len()) {
+            (min, max) if min == max => format!("{}", min),
+            (min, max) => format!("{} to {}", min, max),
+        };
+        return Err(Diagnostic::new(
+            format!(
+                "`{}` takes {} argument(s), but {} were given (usage: .{})",
+                signature.name,
+                expected,
+                args.len(),
+                signature.usage()
+            ),
+            span,
+        ));
+    }
+
+    for (arg, (param, arg_type)) in args.iter().zip(signature.params) {
+        if !arg_type.accepts(&arg.node) {
+            return Err(Diagnostic::new(
+                format!(
+                    "Argument `{}` of `{}` must be {}, but found {} (usage: .{})",
+                    param,
+                    signature.name,
+                    arg_type.describe(),
+                    arg.node,
+                    signature.usage()
+                ),
+                arg.span,
+            ));
+        }
+    }
+
+    (signature.build)(&Args(args))
+}
+
+/// 🔧 모르는 이름 에러 메시지 (비슷한 이름이 있으면 제안, 없으면 목록)
+pub fn unknown_name(kind: &str, name: &str, candidates: &[&str]) -> String {
+    match closest_name(name, candidates) {
+        Some(candidate) => format!("Unknown {} `{}`, did you mean `{}`?", kind, name, candidate),
+        None => format!("Unknown {} `{}` (available: {})", kind, name, candidates.join(", ")),
+    }
+}
+
+/// 🔧 오타로 볼 만큼 비슷한 이름 (편집 거리 2 이하, 없으면 None)
+pub fn closest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .min()
+        .filter(|(distance, _)| *distance <= 2 && *distance < name.chars().count())
+        .map(|(_, candidate)| candidate)
+}
+
+/// 🔧 두 문자열의 편집 거리 (문자 삽입 / 삭제 / 교체 횟수)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
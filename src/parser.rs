@@ -1,43 +1,201 @@
 //! ✅ DSL 파서
 //!
 //! 토큰(Token) 리스트를 의미 있는 명령어(Command)와 표현식(Expression)으로 변환 (AST 생성)
+//! - 모든 Command / Expression 은 Spanned 로 감싸 원본 위치를 유지
 
+use crate::compression::Compression;
+use crate::diagnostic::Diagnostic;
+use crate::format::{ColumnType, Format, ParquetOptions, QuoteStyle, TableOptions};
 use crate::lexer::Token;
+use crate::modifier::{build_modifier, unknown_name, FUNCTIONS};
+use crate::span::{Span, Spanned};
+
+use regex::Regex;
+use serde_json::Value;
 
 // ==========================================================
 // ✅ DSL 내부 구조 정의
 // ==========================================================
 
+/// ✅ 메서드 (`.이름(인자)`, 이름과 인자는 파싱할 때 modifier.rs 에서 검사)
 #[derive(Debug, Clone, PartialEq)]
-pub enum FieldModifier {
+pub enum Method {
     Suffix(String),
     Prefix(String),
     Default(String),
+    Upper,
+    Lower,
+    Trim,
+    TrimStart,
+    Replace(String, String), // (찾을 문자열, 바꿀 문자열)
+    Slice(i64, Option<i64>), // 문자 단위 [start, end), 음수는 끝에서부터
+    Len,                     // 문자 수 (배열 / 객체는 원소 수)
+    PadLeft(usize, char),    // (전체 문자 수, 채울 문자)
+    Repeat(usize),
+    RegexReplace(Pattern, String), // 모두 바꿈, `$1` 로 그룹 참조
+    RegexMatch(Pattern),           // 일치 여부 (bool)
+    RegexExtract(Pattern, usize),  // 첫 일치의 그룹 (0 은 전체, 없으면 null)
+    RegexSplit(Pattern),           // 문자열 배열
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct FieldWithModifiers {
-    pub path: Vec<String>,
-    pub modifiers: Vec<FieldModifier>,
+/// ✅ 파싱할 때 한 번만 컴파일해 두는 정규식 (비교는 패턴 문자열로)
+#[derive(Debug, Clone)]
+pub struct Pattern(pub Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add, // + (숫자끼리는 덧셈, 그 외는 문자열 연결)
+    Sub, // -
+    Mul, // *
+    Div, // /
+    Rem, // %
+    Eq,  // ==
+    Ne,  // !=
+    Lt,  // <
+    Le,  // <=
+    Gt,  // >
+    Ge,  // >=
+    And, // &&
+    Or,  // ||
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Neg, // -
+    Not, // !
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
-    FieldPath(Vec<String>),                       
-    FieldWithModifiers(FieldWithModifiers),       
-    Literal(String),                              
-    Concat(Vec<Expression>),                      
-    RawRecord,                                    
-    Serial,                                       
+    FieldPath(Vec<String>),
+    MethodCall {
+        receiver: Box<Spanned<Expression>>, // 어떤 식이든 가능 (`@a`, `"a"`, `(@a + @b)`, `serial()`)
+        method: Method,
+    },
+    Literal(Value), // 문자열, 숫자, bool, null
+    Binary {
+        op: BinaryOp,
+        left: Box<Spanned<Expression>>,
+        right: Box<Spanned<Expression>>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Spanned<Expression>>,
+    },
+    Conditional {
+        condition: Box<Spanned<Expression>>,
+        then_branch: Box<Spanned<Expression>>,
+        else_branch: Box<Spanned<Expression>>,
+    },
+    RawRecord,
+    Serial(SerialSpec),
+    SourceFile, // source_file() → 현재 레코드의 입력 파일 경로
+    SourceLine, // source_line() → 현재 레코드의 입력 파일 내 줄 번호
+}
+
+/// ✅ serial() 설정 (`serial("train", start: 1000, step: 1, pad: 6, prefix: "Q", by: @category)`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerialSpec {
+    pub counter: SerialCounter,
+    pub start: i64,                          // 첫 번호 (기본 1)
+    pub step: i64,                           // 증가폭 (기본 1)
+    pub pad: Option<usize>,                  // 0 으로 채울 자릿수 (지정하면 문자열)
+    pub prefix: Option<String>,              // 번호 앞에 붙일 문자열 (지정하면 문자열)
+    pub by: Option<Box<Spanned<Expression>>>, // 식의 값마다 따로 번호를 매김
+}
+
+/// ✅ serial 번호를 세는 카운터
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SerialCounter {
+    Site(usize),   // 이름 없는 serial() → 호출 위치마다 따로 (스크립트에 나온 순서)
+    Named(String), // serial("이름") → 같은 이름끼리 번호 공유
+}
+
+/// ✅ serial() 에 쓸 수 있는 옵션 이름
+const SERIAL_OPTIONS: &[&str] = &["start", "step", "pad", "prefix", "by"];
+
+/// ✅ input 명령 설정
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputSpec {
+    pub paths: Vec<String>, // 파일 경로 또는 glob 패턴 (적힌 순서대로 이어 읽음)
+    pub format: Option<Format>, // None 이면 확장자로 판단
+    pub wrap_values: bool, // 객체가 아닌 줄(배열, 숫자 등)을 {"value": ...} 로 감쌀지 여부
+    pub table: TableOptions, // csv / tsv 옵션
+}
+
+/// ✅ output 명령 설정
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputSpec {
+    pub name: Option<String>,             // 이름 있는 출력은 emit / route 로 보낸 레코드만 받음
+    pub path: String,
+    pub format: Option<Format>,           // None 이면 확장자로 판단
+    pub compression: Option<Compression>, // None 이면 확장자로 판단
+    pub level: Option<i32>,               // 압축 레벨 (None 이면 기본값)
+    pub table: TableOptions,              // csv / tsv 옵션
+    pub parquet: ParquetOptions,          // parquet 옵션
+    pub append: bool,                     // 기존 파일 끝에 이어 쓰기 (임시 파일 없이 바로 기록)
+    pub overwrite: Option<OverwritePolicy>, // 기존 파일이 있을 때 (None 이면 always)
+    pub partition: Option<Spanned<Expression>>, // 값마다 다른 파일 (경로의 `{이름}` 자리에 들어감)
+    pub rotate: Option<Rotation>,         // 일정 크기마다 새 파일 (경로의 `{index}` 자리에 번호)
+}
+
+/// ✅ 출력 파일 교체 기준 (rotate every ...)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rotation {
+    Records(usize),   // N 개 레코드마다
+    Megabytes(usize), // 파일 크기가 N MB 를 넘으면
+}
+
+/// ✅ 출력 파일이 이미 있을 때의 처리 방식 (overwrite)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverwritePolicy {
+    Always, // 덮어쓰기 (기본값)
+    Never,  // 에러로 중단
+    Ask,    // 터미널에서 확인
+}
+
+/// ✅ 잘못된 입력 줄 처리 방식 (on_error)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorPolicy {
+    Abort,              // 즉시 중단 (기본값)
+    Skip,               // 건너뛰고 계속
+    Quarantine(String), // 원본 줄과 에러 사유를 별도 파일에 기록하고 계속
+}
+
+/// ✅ emit / route 로 레코드를 보낼 이름 있는 출력 (`good`, `bad { reason = "..."; }`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmitTarget {
+    pub output: Spanned<String>,
+    pub transform: Option<Vec<(String, Spanned<Expression>)>>, // 보내기 전에 적용할 변환 (없으면 그대로)
+}
+
+/// ✅ route 규칙 하나 (`<조건> => 대상, ...;` / `else => 대상;`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteRule {
+    pub condition: Option<Spanned<Expression>>, // None 이면 else
+    pub targets: Vec<EmitTarget>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
-    Input(String),
-    Output(String),
+    Input(InputSpec),
+    Output(OutputSpec),
     Print,
     PrintLine(usize),
-    Transform(Vec<(String, Expression)>),
+    Transform(Vec<(String, Spanned<Expression>)>),
+    Filter(Spanned<Expression>),
+    OnError(ErrorPolicy),
+    Emit {
+        targets: Vec<EmitTarget>,
+        condition: Option<Spanned<Expression>>, // when <조건>
+    },
+    Route(Vec<RouteRule>),
 }
 
 // ==========================================================
@@ -45,74 +203,383 @@ pub enum Command {
 // ==========================================================
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<Spanned<Token>>,
     position: usize,
+    serial_sites: usize, // 지금까지 나온 이름 없는 serial() 수 (호출 위치 번호)
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, position: 0 }
+    pub fn new(tokens: Vec<Spanned<Token>>) -> Self {
+        Self { tokens, position: 0, serial_sites: 0 }
     }
 
+    /// 🔹 현재 토큰 (Eof 이면 None)
     fn current_token(&self) -> Option<&Token> {
-        self.tokens.get(self.position)
+        self.peek_token(0)
+    }
+
+    /// 🔹 현재 위치에서 offset 만큼 앞의 토큰 (Eof 이면 None)
+    fn peek_token(&self, offset: usize) -> Option<&Token> {
+        match self.tokens.get(self.position + offset) {
+            Some(Spanned { node: Token::Eof, .. }) | None => None,
+            Some(spanned) => Some(&spanned.node),
+        }
+    }
+
+    /// 🔹 현재 토큰의 Span (끝이면 마지막 토큰의 Span)
+    fn current_span(&self) -> Span {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map(|t| t.span)
+            .unwrap_or_default()
+    }
+
+    /// 🔹 start 부터 직전에 소비한 토큰까지의 Span
+    fn span_since(&self, start: Span) -> Span {
+        match self.position.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(prev) => start.to(prev.span),
+            None => start,
+        }
+    }
+
+    /// 🔹 현재 위치를 가리키는 에러 생성
+    fn error(&self, message: String) -> Diagnostic {
+        Diagnostic::new(message, self.current_span())
     }
 
     fn advance(&mut self) {
         self.position += 1;
     }
 
-    fn expect(&mut self, expected: &Token) -> Result<(), String> {
+    fn expect(&mut self, expected: &Token) -> Result<(), Diagnostic> {
         match self.current_token() {
             Some(token) if token == expected => {
                 self.advance();
                 Ok(())
             }
-            Some(token) => Err(format!("Expected token {:?}, but found {:?}", expected, token)),
-            None => Err("Expected token but found end of input.".to_string()),
+            Some(token) => Err(self.error(format!("Expected token {:?}, but found {:?}", expected, token))),
+            None => Err(self.error(format!("Expected token {:?}, but found end of input.", expected))),
         }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Command>, String> {
+    pub fn parse(&mut self) -> Result<Vec<Spanned<Command>>, Diagnostic> {
         let mut commands = Vec::new();
 
         while let Some(token) = self.current_token() {
+            let start = self.current_span();
             let command = match token {
                 Token::Input => self.parse_input()?,
                 Token::Output => self.parse_output()?,
                 Token::Print => self.parse_print()?,
                 Token::Transform => self.parse_transform()?,
-                other => return Err(format!("Unexpected token in command position: {:?}", other)),
+                // 📌 filter / where / on_error / emit / route 는 명령 위치에서만 키워드
+                //    (키 이름 `where`, 경로 `@meta.emit` 등으로도 쓸 수 있도록 식별자로 토큰화)
+                Token::Identifier(id) if id == "filter" || id == "where" => self.parse_filter()?,
+                Token::Identifier(id) if id == "on_error" => self.parse_on_error()?,
+                Token::Identifier(id) if id == "emit" => self.parse_emit()?,
+                Token::Identifier(id) if id == "route" => self.parse_route()?,
+                other => return Err(self.error(format!("Unexpected token in command position: {:?}", other))),
             };
-            commands.push(command);
+            commands.push(Spanned::new(command, self.span_since(start)));
         }
 
         Ok(commands)
     }
 
-    fn parse_input(&mut self) -> Result<Command, String> {
+    /// 🔹 `input [형식] "파일명" [옵션...];` / `input ["a.jsonl", "part-*.jsonl"];`
+    ///
+    /// 옵션: `wrap_values`, `with header`, `without header`, `delimiter ";"`
+    fn parse_input(&mut self) -> Result<Command, Diagnostic> {
         self.advance();
-        if let Some(Token::StringLiteral(path)) = self.current_token().cloned() {
-            self.advance();
+        let format = self.parse_format()?;
+
+        let paths = match self.current_token().cloned() {
+            Some(Token::StringLiteral(path)) => {
+                self.advance();
+                Some(vec![path])
+            }
+            Some(Token::LBracket) => Some(self.parse_string_list()?),
+            _ => None,
+        };
+
+        if let Some(paths) = paths {
+            let mut spec = InputSpec { paths, format, ..InputSpec::default() };
+            while let Some(Token::Identifier(option)) = self.current_token().cloned() {
+                match option.as_str() {
+                    "wrap_values" => {
+                        self.advance();
+                        spec.wrap_values = true;
+                    }
+                    "with" | "without" | "delimiter" => self.parse_table_option(&mut spec.table)?,
+                    other => return Err(self.error(format!("Unknown input option '{}'", other))),
+                }
+            }
+
             self.expect(&Token::Semicolon)?;
-            Ok(Command::Input(path))
+            Ok(Command::Input(spec))
         } else {
-            Err(format!("Expected string literal after 'input', but found {:?}", self.current_token()))
+            Err(self.error(format!("Expected string literal after 'input', but found {:?}", self.current_token())))
         }
     }
 
-    fn parse_output(&mut self) -> Result<Command, String> {
+    /// 🔹 `["a", "b", ...]` 문자열 목록 (비어 있으면 에러)
+    fn parse_string_list(&mut self) -> Result<Vec<String>, Diagnostic> {
+        let start = self.current_span();
+        self.expect(&Token::LBracket)?;
+
+        let mut items = Vec::new();
+        loop {
+            match self.current_token().cloned() {
+                Some(Token::RBracket) => break,
+                Some(Token::StringLiteral(s)) => {
+                    self.advance();
+                    items.push(s);
+                }
+                other => return Err(self.error(format!("Expected string literal in list, but found {:?}", other))),
+            }
+
+            match self.current_token() {
+                Some(Token::Comma) => self.advance(),
+                Some(Token::RBracket) => break,
+                other => return Err(self.error(format!("Expected ',' or ']' in list, but found {:?}", other))),
+            }
+        }
+        self.expect(&Token::RBracket)?;
+
+        if items.is_empty() {
+            return Err(Diagnostic::new("List must not be empty", self.span_since(start)));
+        }
+        Ok(items)
+    }
+
+    /// 🔹 `output [이름] [형식] "파일명" [옵션...];`
+    ///
+    /// 옵션: `compress "zstd"`, `level 9`, `with header`, `without header`, `delimiter ";"`,
+    /// `quote "always"`, `columns ["id", "meta.score"]`, `schema { id: int, ... }`, `row_group 10000`,
+    /// `append`, `overwrite never|always|ask`, `partition by <식>`, `rotate every N records|megabytes`
+    fn parse_output(&mut self) -> Result<Command, Diagnostic> {
         self.advance();
+
+        // 형식 이름이 아닌 식별자는 출력 이름 (`output good "ok.jsonl";`)
+        let name = match self.current_token() {
+            Some(Token::Identifier(id)) if Format::from_name(id).is_none() => {
+                let name = id.clone();
+                self.advance();
+                Some(name)
+            }
+            _ => None,
+        };
+        let format = self.parse_format()?;
+
         if let Some(Token::StringLiteral(path)) = self.current_token().cloned() {
             self.advance();
+
+            let mut spec = OutputSpec { name, path, format, ..OutputSpec::default() };
+            while let Some(Token::Identifier(option)) = self.current_token().cloned() {
+                match option.as_str() {
+                    "compress" => {
+                        self.advance();
+                        let Some(Token::StringLiteral(name)) = self.current_token().cloned() else {
+                            return Err(self.error(format!("Expected compression name after 'compress', but found {:?}", self.current_token())));
+                        };
+                        let compression = Compression::from_name(&name).ok_or_else(|| {
+                            self.error(format!("Unknown compression '{}' (expected \"gzip\", \"zstd\", \"bzip2\" or \"none\")", name))
+                        })?;
+                        spec.compression = Some(compression);
+                        self.advance();
+                    }
+                    "level" => {
+                        self.advance();
+                        let Some(Token::Number(level)) = self.current_token().cloned() else {
+                            return Err(self.error(format!("Expected number after 'level', but found {:?}", self.current_token())));
+                        };
+                        spec.level = Some(level.min(i32::MAX as usize) as i32);
+                        self.advance();
+                    }
+                    "quote" => {
+                        self.advance();
+                        let Some(Token::StringLiteral(name)) = self.current_token().cloned() else {
+                            return Err(self.error(format!("Expected quote style after 'quote', but found {:?}", self.current_token())));
+                        };
+                        let quote = QuoteStyle::from_name(&name).ok_or_else(|| {
+                            self.error(format!("Unknown quote style '{}' (expected \"necessary\", \"always\", \"non_numeric\" or \"never\")", name))
+                        })?;
+                        spec.table.quote = Some(quote);
+                        self.advance();
+                    }
+                    "columns" => {
+                        self.advance();
+                        spec.table.columns = Some(self.parse_string_list()?);
+                    }
+                    "schema" => {
+                        self.advance();
+                        spec.parquet.schema = Some(self.parse_schema_fields()?);
+                    }
+                    "row_group" => {
+                        self.advance();
+                        let Some(Token::Number(size)) = self.current_token().cloned() else {
+                            return Err(self.error(format!("Expected number after 'row_group', but found {:?}", self.current_token())));
+                        };
+                        if size == 0 {
+                            return Err(self.error("Row group size must be at least 1".to_string()));
+                        }
+                        spec.parquet.row_group_size = Some(size);
+                        self.advance();
+                    }
+                    "append" => {
+                        if spec.overwrite.is_some() {
+                            return Err(self.error("'append' cannot be combined with 'overwrite'".to_string()));
+                        }
+                        self.advance();
+                        spec.append = true;
+                    }
+                    "overwrite" => {
+                        if spec.append {
+                            return Err(self.error("'overwrite' cannot be combined with 'append'".to_string()));
+                        }
+                        self.advance();
+                        let policy = match self.current_token() {
+                            Some(Token::Identifier(id)) if id == "always" => OverwritePolicy::Always,
+                            Some(Token::Identifier(id)) if id == "never" => OverwritePolicy::Never,
+                            Some(Token::Identifier(id)) if id == "ask" => OverwritePolicy::Ask,
+                            other => {
+                                return Err(self.error(format!("Expected 'never', 'always' or 'ask' after 'overwrite', but found {:?}", other)));
+                            }
+                        };
+                        self.advance();
+                        spec.overwrite = Some(policy);
+                    }
+                    "partition" => {
+                        self.advance();
+                        self.expect_keyword("by")?;
+                        spec.partition = Some(self.parse_expression()?);
+                    }
+                    "rotate" => {
+                        self.advance();
+                        self.expect_keyword("every")?;
+                        let Some(Token::Number(size)) = self.current_token().cloned() else {
+                            return Err(self.error(format!("Expected number after 'rotate every', but found {:?}", self.current_token())));
+                        };
+                        if size == 0 {
+                            return Err(self.error("Rotation size must be at least 1".to_string()));
+                        }
+                        self.advance();
+                        let rotation = match self.current_token() {
+                            Some(Token::Identifier(unit)) if unit == "records" => Rotation::Records(size),
+                            Some(Token::Identifier(unit)) if unit == "megabytes" || unit == "mb" => Rotation::Megabytes(size),
+                            other => {
+                                return Err(self.error(format!("Expected 'records' or 'megabytes' after 'rotate every {}', but found {:?}", size, other)));
+                            }
+                        };
+                        self.advance();
+                        spec.rotate = Some(rotation);
+                    }
+                    "with" | "without" | "delimiter" => self.parse_table_option(&mut spec.table)?,
+                    other => return Err(self.error(format!("Unknown output option '{}'", other))),
+                }
+            }
+
             self.expect(&Token::Semicolon)?;
-            Ok(Command::Output(path))
+            Ok(Command::Output(spec))
         } else {
-            Err(format!("Expected string literal after 'output', but found {:?}", self.current_token()))
+            Err(self.error(format!("Expected string literal after 'output', but found {:?}", self.current_token())))
         }
     }
 
-    fn parse_print(&mut self) -> Result<Command, String> {
+    /// 🔹 input / output 뒤의 형식 이름 (`jsonl`, `json [pretty]`, `csv`, `tsv`), 없으면 None
+    fn parse_format(&mut self) -> Result<Option<Format>, Diagnostic> {
+        let Some(Token::Identifier(name)) = self.current_token().cloned() else {
+            return Ok(None);
+        };
+        let format = Format::from_name(&name).ok_or_else(|| {
+            self.error(format!("Unknown format '{}' (expected jsonl, json, csv, tsv or parquet)", name))
+        })?;
+        self.advance();
+
+        if format == Format::Json
+            && let Some(Token::Identifier(id)) = self.current_token()
+            && id == "pretty"
+        {
+            self.advance();
+            return Ok(Some(Format::JsonPretty));
+        }
+        Ok(Some(format))
+    }
+
+    /// 🔹 input / output 공통 csv 옵션: `with header`, `without header`, `delimiter ";"`
+    fn parse_table_option(&mut self, table: &mut TableOptions) -> Result<(), Diagnostic> {
+        match self.current_token().cloned() {
+            Some(Token::Identifier(id)) if id == "with" || id == "without" => {
+                self.advance();
+                self.expect_keyword("header")?;
+                table.header = Some(id == "with");
+            }
+            Some(Token::Identifier(id)) if id == "delimiter" => {
+                self.advance();
+                let delimiter = match self.current_token() {
+                    Some(Token::StringLiteral(s)) if s.len() == 1 && s.is_ascii() => s.as_bytes()[0],
+                    other => {
+                        return Err(self.error(format!("Expected a single ASCII character after 'delimiter', but found {:?}", other)));
+                    }
+                };
+                table.delimiter = Some(delimiter);
+                self.advance();
+            }
+            other => return Err(self.error(format!("Unexpected option {:?}", other))),
+        }
+        Ok(())
+    }
+
+    /// 🔹 parquet 스키마의 열 목록 `{ 이름: 타입, ... }`
+    fn parse_schema_fields(&mut self) -> Result<Vec<(String, ColumnType)>, Diagnostic> {
+        let start = self.current_span();
+        self.expect(&Token::LBrace)?;
+
+        let mut fields: Vec<(String, ColumnType)> = Vec::new();
+        while let Some(Token::Identifier(name)) = self.current_token().cloned() {
+            if fields.iter().any(|(existing, _)| *existing == name) {
+                return Err(self.error(format!("Duplicate schema field '{}'", name)));
+            }
+            self.advance();
+            self.expect(&Token::Colon)?;
+            fields.push((name, self.parse_column_type()?));
+
+            match self.current_token() {
+                Some(Token::Comma) => self.advance(),
+                _ => break,
+            }
+        }
+        self.expect(&Token::RBrace)?;
+
+        if fields.is_empty() {
+            return Err(Diagnostic::new("Schema must declare at least one field", self.span_since(start)));
+        }
+        Ok(fields)
+    }
+
+    /// 🔹 parquet 열 타입: `string` / `int` / `float` / `bool` / `[타입]` / `{ 이름: 타입, ... }`
+    fn parse_column_type(&mut self) -> Result<ColumnType, Diagnostic> {
+        match self.current_token().cloned() {
+            Some(Token::Identifier(name)) => {
+                let column_type = ColumnType::from_name(&name).ok_or_else(|| {
+                    self.error(format!("Unknown column type '{}' (expected string, int, float or bool)", name))
+                })?;
+                self.advance();
+                Ok(column_type)
+            }
+            Some(Token::LBracket) => {
+                self.advance();
+                let item = self.parse_column_type()?;
+                self.expect(&Token::RBracket)?;
+                Ok(ColumnType::List(Box::new(item)))
+            }
+            Some(Token::LBrace) => Ok(ColumnType::Struct(self.parse_schema_fields()?)),
+            other => Err(self.error(format!("Expected column type, but found {:?}", other))),
+        }
+    }
+
+    fn parse_print(&mut self) -> Result<Command, Diagnostic> {
         self.advance();
         match self.current_token() {
             Some(Token::Semicolon) => {
@@ -126,15 +593,20 @@ impl Parser {
                     self.expect(&Token::Semicolon)?;
                     Ok(Command::PrintLine(n))
                 } else {
-                    Err(format!("Expected number after 'print line', but found {:?}", self.current_token()))
+                    Err(self.error(format!("Expected number after 'print line', but found {:?}", self.current_token())))
                 }
             }
-            other => Err(format!("Unexpected token after 'print': {:?}", other)),
+            other => Err(self.error(format!("Unexpected token after 'print': {:?}", other))),
         }
     }
 
-    fn parse_transform(&mut self) -> Result<Command, String> {
+    fn parse_transform(&mut self) -> Result<Command, Diagnostic> {
         self.advance();
+        Ok(Command::Transform(self.parse_assignments()?))
+    }
+
+    /// 🔹 `{ 키 = 식; ... }` 변환 블록 (transform, emit / route 대상에서 사용)
+    fn parse_assignments(&mut self) -> Result<Vec<(String, Spanned<Expression>)>, Diagnostic> {
         self.expect(&Token::LBrace)?;
 
         let mut transforms = Vec::new();
@@ -154,122 +626,502 @@ impl Parser {
                     transforms.push((key, expr));
                 }
                 other => {
-                    return Err(format!("Unexpected token inside transform block: {:?}", other));
+                    return Err(self.error(format!("Unexpected token inside transform block: {:?}", other)));
                 }
             }
         }
 
-        Ok(Command::Transform(transforms))
+        Ok(transforms)
     }
 
-    fn parse_modifiers(&mut self) -> Result<Vec<FieldModifier>, String> {
-        let mut modifiers = Vec::new();
+    /// 🔹 `emit to 이름 [{ ... }], ... [when <조건>];`
+    fn parse_emit(&mut self) -> Result<Command, Diagnostic> {
+        self.advance();
+        self.expect_keyword("to")?;
+        let targets = self.parse_emit_targets()?;
 
-        while let Some(Token::Dot) = self.current_token() {
-            let lookahead1 = self.tokens.get(self.position + 1).cloned();
-            let lookahead2 = self.tokens.get(self.position + 2).cloned();
+        let condition = match self.current_token() {
+            Some(Token::Identifier(id)) if id == "when" => {
+                self.advance();
+                Some(self.parse_expression()?)
+            }
+            _ => None,
+        };
+        self.expect(&Token::Semicolon)?;
+        Ok(Command::Emit { targets, condition })
+    }
 
-            match (lookahead1, lookahead2) {
-                (Some(Token::Identifier(name)), Some(Token::LParen)) => {
-                    self.advance();
+    /// 🔹 `route { <조건> => 대상, ...; ... else => 대상; }` (처음으로 맞는 규칙 하나만 적용)
+    fn parse_route(&mut self) -> Result<Command, Diagnostic> {
+        let start = self.current_span();
+        self.advance();
+        self.expect(&Token::LBrace)?;
+
+        let mut rules: Vec<RouteRule> = Vec::new();
+        loop {
+            let condition = match self.current_token() {
+                Some(Token::RBrace) => break,
+                Some(Token::Identifier(id)) if id == "else" => {
+                    if rules.iter().any(|rule| rule.condition.is_none()) {
+                        return Err(self.error("Only one 'else' rule is allowed in 'route'".to_string()));
+                    }
                     self.advance();
-                    let modifier_name = name;
+                    None
+                }
+                _ => Some(self.parse_expression()?),
+            };
+            self.expect(&Token::FatArrow)?;
+            let targets = self.parse_emit_targets()?;
+            self.expect(&Token::Semicolon)?;
+            rules.push(RouteRule { condition, targets });
+        }
+        self.expect(&Token::RBrace)?;
 
-                    self.expect(&Token::LParen)?;
-                    let value = match self.current_token().cloned() {
-                        Some(Token::StringLiteral(s)) => {
-                            self.advance();
-                            s
-                        }
-                        _ => break,
-                    };
-                    self.expect(&Token::RParen)?;
+        if rules.is_empty() {
+            return Err(Diagnostic::new("'route' must have at least one rule", self.span_since(start)));
+        }
+        if let Some(i) = rules.iter().position(|rule| rule.condition.is_none())
+            && i != rules.len() - 1
+        {
+            return Err(Diagnostic::new("'else' must be the last rule in 'route'", self.span_since(start)));
+        }
+        Ok(Command::Route(rules))
+    }
 
-                    let modifier = match modifier_name.as_str() {
-                        "prefix" => FieldModifier::Prefix(value),
-                        "suffix" => FieldModifier::Suffix(value),
-                        "default" => FieldModifier::Default(value),
-                        _ => break,
-                    };
+    /// 🔹 `이름 [{ ... }], 이름 [{ ... }], ...`
+    fn parse_emit_targets(&mut self) -> Result<Vec<EmitTarget>, Diagnostic> {
+        let mut targets = Vec::new();
+        loop {
+            let Some(Token::Identifier(name)) = self.current_token().cloned() else {
+                return Err(self.error(format!("Expected output name, but found {:?}", self.current_token())));
+            };
+            let output = Spanned::new(name, self.current_span());
+            self.advance();
 
-                    modifiers.push(modifier);
-                }
+            let transform = match self.current_token() {
+                Some(Token::LBrace) => Some(self.parse_assignments()?),
+                _ => None,
+            };
+            targets.push(EmitTarget { output, transform });
+
+            match self.current_token() {
+                Some(Token::Comma) => self.advance(),
                 _ => break,
             }
         }
-
-        Ok(modifiers)
+        Ok(targets)
     }
 
-    fn parse_expression(&mut self) -> Result<Expression, String> {
-        let mut parts = Vec::new();
+    /// 🔹 `filter <조건>;` (`where <조건>;` 도 동일)
+    fn parse_filter(&mut self) -> Result<Command, Diagnostic> {
+        self.advance();
+        let condition = self.parse_expression()?;
+        self.expect(&Token::Semicolon)?;
+        Ok(Command::Filter(condition))
+    }
 
-        loop {
-            let expr = match self.current_token() {
-                Some(Token::Field(first)) => {
-                    let mut path = vec![first.clone()];
+    /// 🔹 `on_error abort;` / `on_error skip;` / `on_error quarantine "bad.jsonl";`
+    fn parse_on_error(&mut self) -> Result<Command, Diagnostic> {
+        self.advance();
+        let policy = match self.current_token().cloned() {
+            Some(Token::Identifier(id)) if id == "abort" => {
+                self.advance();
+                ErrorPolicy::Abort
+            }
+            Some(Token::Identifier(id)) if id == "skip" => {
+                self.advance();
+                ErrorPolicy::Skip
+            }
+            Some(Token::Identifier(id)) if id == "quarantine" => {
+                self.advance();
+                if let Some(Token::StringLiteral(path)) = self.current_token().cloned() {
                     self.advance();
+                    ErrorPolicy::Quarantine(path)
+                } else {
+                    return Err(self.error(format!("Expected string literal after 'on_error quarantine', but found {:?}", self.current_token())));
+                }
+            }
+            other => return Err(self.error(format!("Expected 'abort', 'skip' or 'quarantine' after 'on_error', but found {:?}", other))),
+        };
+        self.expect(&Token::Semicolon)?;
+        Ok(Command::OnError(policy))
+    }
 
-                    while let Some(Token::Dot) = self.current_token() {
-                        let lookahead1 = self.tokens.get(self.position + 1).cloned();
-                        let lookahead2 = self.tokens.get(self.position + 2).cloned();
-
-                        match (lookahead1, lookahead2) {
-                            (Some(Token::Identifier(_)), Some(Token::LParen)) => break,
-                            (Some(Token::Identifier(id)), _) => {
-                                self.advance();
-                                self.advance();
-                                path.push(id);
-                            }
-                            _ => break,
-                        }
-                    }
+    /// 🔹 `serial(["이름"], [start: N], [step: N], [pad: N], [prefix: "..."], [by: <식>])`
+    fn parse_serial(&mut self) -> Result<Expression, Diagnostic> {
+        self.advance();
+        self.expect(&Token::LParen)?;
 
-                    let modifiers = self.parse_modifiers()?;
-                    if modifiers.is_empty() {
-                        Expression::FieldPath(path)
-                    } else {
-                        Expression::FieldWithModifiers(FieldWithModifiers { path, modifiers })
-                    }
+        let counter = match self.current_token().cloned() {
+            Some(Token::StringLiteral(name)) => {
+                self.advance();
+                if !matches!(self.current_token(), Some(Token::RParen)) {
+                    self.expect(&Token::Comma)?;
                 }
+                SerialCounter::Named(name)
+            }
+            _ => {
+                self.serial_sites += 1;
+                SerialCounter::Site(self.serial_sites)
+            }
+        };
+        let mut spec = SerialSpec { counter, start: 1, step: 1, pad: None, prefix: None, by: None };
 
-                Some(Token::StringLiteral(s)) => {
-                    let s = s.clone();
-                    self.advance();
-                    Expression::Literal(s)
-                }
+        let mut seen: Vec<String> = Vec::new();
+        while let Some(Token::Identifier(option)) = self.current_token().cloned() {
+            let option_span = self.current_span();
+            if !SERIAL_OPTIONS.contains(&option.as_str()) {
+                return Err(Diagnostic::new(unknown_name("serial option", &option, SERIAL_OPTIONS), option_span));
+            }
+            if seen.contains(&option) {
+                return Err(Diagnostic::new(format!("Duplicate serial option '{}'", option), option_span));
+            }
+            self.advance();
+            self.expect(&Token::Colon)?;
 
-                Some(Token::Identifier(id)) if id == "raw" => {
+            match option.as_str() {
+                "start" => spec.start = self.parse_integer(&option)?,
+                "step" => spec.step = self.parse_integer(&option)?,
+                "pad" => {
+                    let value_span = self.current_span();
+                    let pad = self.parse_integer(&option)?;
+                    let pad = usize::try_from(pad)
+                        .map_err(|_| Diagnostic::new("'pad' must not be negative", value_span))?;
+                    spec.pad = Some(pad);
+                }
+                "prefix" => {
+                    let Some(Token::StringLiteral(prefix)) = self.current_token().cloned() else {
+                        return Err(self.error(format!("Expected string after 'prefix:', but found {:?}", self.current_token())));
+                    };
                     self.advance();
-                    self.expect(&Token::LParen)?;
-                    self.expect(&Token::RParen)?;
-                    Expression::RawRecord
+                    spec.prefix = Some(prefix);
                 }
+                _ => spec.by = Some(Box::new(self.parse_expression()?)),
+            }
+            seen.push(option);
 
-                Some(Token::Identifier(id)) if id == "serial" => {
+            match self.current_token() {
+                Some(Token::Comma) => self.advance(),
+                _ => break,
+            }
+        }
+
+        self.expect(&Token::RParen)?;
+        Ok(Expression::Serial(spec))
+    }
+
+    /// 🔹 정수 리터럴 (음수 가능, `option` 은 에러 메시지용)
+    fn parse_integer(&mut self, option: &str) -> Result<i64, Diagnostic> {
+        let negative = matches!(self.current_token(), Some(Token::Minus));
+        if negative {
+            self.advance();
+        }
+        let Some(Token::Number(n)) = self.current_token().cloned() else {
+            return Err(self.error(format!("Expected integer after '{}:', but found {:?}", option, self.current_token())));
+        };
+        self.advance();
+
+        let n = n.min(i64::MAX as usize) as i64;
+        Ok(if negative { -n } else { n })
+    }
+
+    /// 🔹 식 뒤에 이어지는 메서드 호출 (`.trim().upper()` → 왼쪽부터 차례로 감쌈)
+    fn parse_method_calls(
+        &mut self,
+        mut receiver: Spanned<Expression>,
+        start: Span,
+    ) -> Result<Spanned<Expression>, Diagnostic> {
+        while let Some(Token::Dot) = self.current_token() {
+            let lookahead1 = self.peek_token(1).cloned();
+            let lookahead2 = self.peek_token(2).cloned();
+
+            match (lookahead1, lookahead2) {
+                (Some(Token::Identifier(name)), Some(Token::LParen)) => {
+                    let call_start = self.current_span();
+                    self.advance();
+                    let name = Spanned::new(name, self.current_span());
                     self.advance();
-                    self.expect(&Token::LParen)?;
-                    self.expect(&Token::RParen)?;
-                    Expression::Serial
+                    let args = self.parse_modifier_args()?;
+                    let method = build_modifier(&name, &args, self.span_since(call_start))?;
+
+                    let expr = Expression::MethodCall { receiver: Box::new(receiver), method };
+                    receiver = Spanned::new(expr, self.span_since(start));
                 }
+                _ => break,
+            }
+        }
 
-                other => return Err(format!("Unexpected token in expression: {:?}", other)),
-            };
+        Ok(receiver)
+    }
 
-            parts.push(expr);
+    /// 🔹 수정자 인자 목록 `(리터럴, ...)` (문자열, 정수, 음의 정수)
+    fn parse_modifier_args(&mut self) -> Result<Vec<Spanned<Value>>, Diagnostic> {
+        self.expect(&Token::LParen)?;
 
-            match self.current_token() {
-                Some(Token::Plus) => {
+        let mut args = Vec::new();
+        while !matches!(self.current_token(), Some(Token::RParen)) {
+            let start = self.current_span();
+            let value = match self.current_token().cloned() {
+                Some(Token::StringLiteral(s)) => Value::String(s),
+                Some(Token::Number(n)) => Value::from(n),
+                Some(Token::Minus) if let Some(Token::Number(n)) = self.peek_token(1).cloned() => {
                     self.advance();
+                    let Some(n) = i64::try_from(n).ok().and_then(i64::checked_neg) else {
+                        return Err(Diagnostic::new(
+                            format!("Integer argument -{} is out of range", n),
+                            start.to(self.current_span()),
+                        ));
+                    };
+                    Value::from(n)
+                }
+                other => {
+                    return Err(self.error(format!("Expected string or integer argument, but found {:?}", other)));
                 }
+            };
+            self.advance();
+            args.push(Spanned::new(value, self.span_since(start)));
+
+            match self.current_token() {
+                Some(Token::Comma) => self.advance(),
                 _ => break,
             }
         }
 
-        if parts.len() == 1 {
-            Ok(parts.remove(0))
-        } else {
-            Ok(Expression::Concat(parts))
+        self.expect(&Token::RParen)?;
+        Ok(args)
+    }
+
+    /// 🔹 표현식 파싱 (Pratt 방식, 우선순위는 infix_binding_power 참고)
+    fn parse_expression(&mut self) -> Result<Spanned<Expression>, Diagnostic> {
+        self.parse_expression_bp(0)
+    }
+
+    /// 🔹 min_bp 보다 강하게 결합하는 연산자까지만 읽어 하나의 표현식으로 묶음
+    fn parse_expression_bp(&mut self, min_bp: u8) -> Result<Spanned<Expression>, Diagnostic> {
+        let start = self.current_span();
+        let mut left = self.parse_prefix()?;
+
+        loop {
+            // 삼항 연산자: cond ? a : b (우결합, 가장 낮은 우선순위)
+            if let Some(Token::Question) = self.current_token() {
+                if TERNARY_BP < min_bp {
+                    break;
+                }
+                self.advance();
+                let then_branch = self.parse_expression_bp(0)?;
+                self.expect(&Token::Colon)?;
+                let else_branch = self.parse_expression_bp(TERNARY_BP)?;
+                let expr = Expression::Conditional {
+                    condition: Box::new(left),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                };
+                left = Spanned::new(expr, self.span_since(start));
+                continue;
+            }
+
+            let Some((op, left_bp, right_bp)) = self.current_token().and_then(infix_binding_power) else {
+                break;
+            };
+            if left_bp < min_bp {
+                break;
+            }
+
+            self.advance();
+            let right = self.parse_expression_bp(right_bp)?;
+            let expr = Expression::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+            left = Spanned::new(expr, self.span_since(start));
         }
+
+        Ok(left)
+    }
+
+    /// 🔹 전위 요소: 단항 연산자(`-`, `!`), `if ... then ... else ...`, 기본 표현식
+    fn parse_prefix(&mut self) -> Result<Spanned<Expression>, Diagnostic> {
+        let start = self.current_span();
+
+        let op = match self.current_token() {
+            Some(Token::Minus) => UnaryOp::Neg,
+            Some(Token::Bang) => UnaryOp::Not,
+            Some(Token::Identifier(id)) if id == "if" => return self.parse_if(),
+            _ => return self.parse_primary(),
+        };
+
+        self.advance();
+        let operand = self.parse_expression_bp(PREFIX_BP)?;
+        let expr = Expression::Unary {
+            op,
+            operand: Box::new(operand),
+        };
+        Ok(Spanned::new(expr, self.span_since(start)))
+    }
+
+    /// 🔹 `if <cond> then <expr> else <expr>` (else 쪽은 가능한 한 길게 읽음)
+    fn parse_if(&mut self) -> Result<Spanned<Expression>, Diagnostic> {
+        let start = self.current_span();
+        self.advance();
+
+        let condition = self.parse_expression_bp(0)?;
+        self.expect_keyword("then")?;
+        let then_branch = self.parse_expression_bp(0)?;
+        self.expect_keyword("else")?;
+        let else_branch = self.parse_expression_bp(0)?;
+
+        let expr = Expression::Conditional {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        };
+        Ok(Spanned::new(expr, self.span_since(start)))
     }
+
+    /// 🔹 식별자 형태의 키워드(then, else 등) 확인 후 소비
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Diagnostic> {
+        match self.current_token() {
+            Some(Token::Identifier(id)) if id == keyword => {
+                self.advance();
+                Ok(())
+            }
+            other => Err(self.error(format!("Expected '{}', but found {:?}", keyword, other))),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Spanned<Expression>, Diagnostic> {
+        let start = self.current_span();
+        let expr = match self.current_token() {
+            Some(Token::Field(first)) => {
+                let mut path = vec![first.clone()];
+                self.advance();
+
+                while let Some(Token::Dot) = self.current_token() {
+                    let lookahead1 = self.peek_token(1).cloned();
+                    let lookahead2 = self.peek_token(2).cloned();
+
+                    match (lookahead1, lookahead2) {
+                        (Some(Token::Identifier(_)), Some(Token::LParen)) => break,
+                        (Some(Token::Identifier(id)), _) => {
+                            self.advance();
+                            self.advance();
+                            path.push(id);
+                        }
+                        _ => break,
+                    }
+                }
+
+                Expression::FieldPath(path)
+            }
+
+            Some(Token::StringLiteral(s)) => {
+                let value = Value::String(s.clone());
+                self.advance();
+                Expression::Literal(value)
+            }
+
+            Some(Token::Number(n)) => {
+                let value = Value::from(*n);
+                self.advance();
+                Expression::Literal(value)
+            }
+
+            Some(Token::Float(f)) => {
+                let value = Value::from(*f);
+                self.advance();
+                Expression::Literal(value)
+            }
+
+            Some(Token::Identifier(id)) if id == "true" || id == "false" => {
+                let value = Value::Bool(id == "true");
+                self.advance();
+                Expression::Literal(value)
+            }
+
+            Some(Token::Identifier(id)) if id == "null" => {
+                self.advance();
+                Expression::Literal(Value::Null)
+            }
+
+            Some(Token::Identifier(id)) if id == "raw" => {
+                self.advance();
+                self.expect(&Token::LParen)?;
+                self.expect(&Token::RParen)?;
+                Expression::RawRecord
+            }
+
+            Some(Token::Identifier(id)) if id == "serial" => self.parse_serial()?,
+
+            Some(Token::Identifier(id)) if id == "source_file" => {
+                self.advance();
+                self.expect(&Token::LParen)?;
+                self.expect(&Token::RParen)?;
+                Expression::SourceFile
+            }
+
+            Some(Token::Identifier(id)) if id == "source_line" => {
+                self.advance();
+                self.expect(&Token::LParen)?;
+                self.expect(&Token::RParen)?;
+                Expression::SourceLine
+            }
+
+            // 괄호: 내부 표현식 그대로, Span 만 괄호까지 포함
+            Some(Token::LParen) => {
+                self.advance();
+                let inner = self.parse_expression()?;
+                self.expect(&Token::RParen)?;
+                inner.node
+            }
+
+            Some(Token::Identifier(id)) if matches!(self.peek_token(1), Some(Token::LParen)) => {
+                return Err(self.error(unknown_name("function", id, FUNCTIONS)));
+            }
+
+            other => return Err(self.error(format!("Unexpected token in expression: {:?}", other))),
+        };
+        let expr = Spanned::new(expr, self.span_since(start));
+
+        self.parse_method_calls(expr, start)
+    }
+}
+
+// ==========================================================
+// ✅ 연산자 우선순위 (숫자가 클수록 강하게 결합)
+// ==========================================================
+
+const TERNARY_BP: u8 = 1;
+const PREFIX_BP: u8 = 15;
+
+/// 🔹 이항 연산자의 (연산자, 왼쪽 결합력, 오른쪽 결합력), 모두 좌결합
+///
+/// | 우선순위 | 연산자              |
+/// |----------|---------------------|
+/// | 낮음     | `? :`, `if`         |
+/// |          | `\|\|`              |
+/// |          | `&&`                |
+/// |          | `==` `!=`           |
+/// |          | `<` `<=` `>` `>=`   |
+/// |          | `+` `-`             |
+/// |          | `*` `/` `%`         |
+/// | 높음     | 단항 `-` `!`        |
+fn infix_binding_power(token: &Token) -> Option<(BinaryOp, u8, u8)> {
+    let (op, bp) = match token {
+        Token::OrOr => (BinaryOp::Or, 3),
+        Token::AndAnd => (BinaryOp::And, 5),
+        Token::EqualEqual => (BinaryOp::Eq, 7),
+        Token::NotEqual => (BinaryOp::Ne, 7),
+        Token::Less => (BinaryOp::Lt, 9),
+        Token::LessEqual => (BinaryOp::Le, 9),
+        Token::Greater => (BinaryOp::Gt, 9),
+        Token::GreaterEqual => (BinaryOp::Ge, 9),
+        Token::Plus => (BinaryOp::Add, 11),
+        Token::Minus => (BinaryOp::Sub, 11),
+        Token::Star => (BinaryOp::Mul, 13),
+        Token::Slash => (BinaryOp::Div, 13),
+        Token::Percent => (BinaryOp::Rem, 13),
+        _ => return None,
+    };
+    Some((op, bp, bp + 1))
 }
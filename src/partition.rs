@@ -0,0 +1,278 @@
+//! ✅ partition.rs
+//!
+//! 출력 나누기 (partition by / rotate every)
+//! - `partition by <식>`: 레코드마다 식을 평가해 값별로 다른 파일에 기록
+//!   (경로의 `{이름}` 자리에 값이 들어감, `"out/{category}.jsonl"`)
+//! - `rotate every N records|megabytes`: 파일이 기준을 넘으면 번호를 올려 새 파일
+//!   (경로의 `{index}` 자리에 00000, 00001, ... 이 들어감)
+//! - 동시에 열어 두는 파일은 최대 MAX_OPEN_FILES 개
+//!   넘으면 가장 오래 쓰지 않은 파일을 잠시 닫았다가, 다시 필요할 때 이어 씀
+//! - 모든 파일은 실행이 끝났을 때 임시 파일에서 제자리로 교체 (rotate 로 다 찬 파일은 바로 교체)
+
+use crate::evaluator::Record;
+use crate::parser::{OutputSpec, Rotation};
+use crate::sink::{RecordWriter, SuspendedFile, STDOUT_PATH};
+
+use indexmap::IndexMap;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// ✅ 동시에 열어 두는 출력 파일 수 상한
+const MAX_OPEN_FILES: usize = 64;
+
+/// ✅ 여러 파일로 나누어 기록하는 작성기 (나누지 않으면 파일 하나)
+pub struct PartitionedWriter {
+    spec: OutputSpec,
+    input_paths: Vec<String>,
+    open: IndexMap<String, RecordWriter>,       // 경로 → 열린 파일 (앞쪽일수록 오래 쓰지 않음)
+    suspended: HashMap<String, SuspendedFile>,  // 경로 → 잠시 닫아 둔 파일
+    partitions: HashMap<String, PartitionState>, // 정리한 파티션 값 → 현재 파일 상태
+    files: Vec<String>,                         // 만든 파일 (만든 순서, 보고용)
+}
+
+/// ✅ 파티션별 현재 파일
+#[derive(Default)]
+struct PartitionState {
+    index: usize,   // rotate 번호
+    records: usize, // 현재 파일에 기록한 레코드 수
+}
+
+impl PartitionedWriter {
+    /// 🔹 작성기 생성 (나누지 않는 출력이면 입력이 없어도 빈 파일이 생기도록 바로 생성)
+    pub fn create(spec: &OutputSpec, input_paths: &[String]) -> Result<Self, String> {
+        let placeholders = placeholders(&spec.path);
+
+        if spec.path == STDOUT_PATH && (spec.partition.is_some() || spec.rotate.is_some()) {
+            return Err("Cannot split standard output into multiple files".to_string());
+        }
+        if spec.partition.is_some() && !placeholders.iter().any(|name| *name != "index") {
+            return Err(format!(
+                "'partition by' needs a placeholder such as '{{category}}' in the output path '{}'",
+                spec.path
+            ));
+        }
+        if spec.rotate.is_some() && !placeholders.contains(&"index") {
+            return Err(format!("'rotate' needs '{{index}}' in the output path '{}'", spec.path));
+        }
+
+        let mut writer = Self {
+            spec: spec.clone(),
+            input_paths: input_paths.to_vec(),
+            open: IndexMap::new(),
+            suspended: HashMap::new(),
+            partitions: HashMap::new(),
+            files: Vec::new(),
+        };
+
+        if !writer.is_split() {
+            writer.writer_for(&spec.path)?;
+        }
+        Ok(writer)
+    }
+
+    /// 🔹 여러 파일로 나누는 출력인지
+    pub fn is_split(&self) -> bool {
+        self.spec.partition.is_some() || self.spec.rotate.is_some()
+    }
+
+    /// 🔹 레코드 하나 기록 (`key` 는 partition by 식을 평가한 값)
+    pub fn write(&mut self, key: Option<&str>, record: &Record) -> Result<(), String> {
+        if !self.is_split() {
+            let path = self.spec.path.clone();
+            return self.writer_for(&path)?.write(record);
+        }
+
+        // 파일 이름과 같은 기준(정리한 값)으로 상태를 관리해야 `a/b` 와 `a_b` 처럼
+        // 같은 파일로 가는 값이 서로의 파일을 다시 만들어 덮어쓰지 않음
+        let key = sanitize(key.unwrap_or_default());
+        let key = key.as_str();
+        let state = self.partitions.entry(key.to_string()).or_default();
+        let (mut index, mut records) = (state.index, state.records);
+
+        let mut path = render_path(&self.spec.path, key, index);
+        if let Some(rotation) = self.spec.rotate
+            && records > 0
+        {
+            let writer = self.writer_for(&path)?;
+            let full = match rotation {
+                Rotation::Records(limit) => records >= limit,
+                Rotation::Megabytes(limit) => writer.bytes_written() >= limit as u64 * 1024 * 1024,
+            };
+            if full {
+                if let Some(writer) = self.open.shift_remove(&path) {
+                    writer.finish()?;
+                }
+                index += 1;
+                records = 0;
+                path = render_path(&self.spec.path, key, index);
+            }
+        }
+
+        self.writer_for(&path)?.write(record)?;
+
+        let state = self.partitions.entry(key.to_string()).or_default();
+        state.index = index;
+        state.records = records + 1;
+        Ok(())
+    }
+
+    /// 🔹 모든 파일 마무리 후 제자리로 교체, 만든 파일 목록 반환
+    pub fn finish(self) -> Result<Vec<String>, String> {
+        let Self { open, suspended, files, .. } = self;
+
+        for (_, writer) in open {
+            writer.finish()?;
+        }
+        for (_, file) in suspended {
+            file.commit()?;
+        }
+        Ok(files)
+    }
+
+    /// 🔹 경로에 해당하는 열린 작성기 (없으면 새로 만들거나 잠시 닫아 둔 파일을 다시 엶)
+    fn writer_for(&mut self, path: &str) -> Result<&mut RecordWriter, String> {
+        if let Some(index) = self.open.get_index_of(path) {
+            let last = self.open.len() - 1;
+            self.open.move_index(index, last);
+            return Ok(&mut self.open[last]);
+        }
+
+        if self.open.len() >= MAX_OPEN_FILES {
+            self.evict()?;
+        }
+
+        let mut spec = self.spec.clone();
+        spec.path = path.to_string();
+
+        let writer = match self.suspended.remove(path) {
+            Some(suspended) => RecordWriter::resume(&spec, suspended)?,
+            None => {
+                if self.is_split()
+                    && let Some(parent) = Path::new(path).parent()
+                    && !parent.as_os_str().is_empty()
+                {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+                }
+                let writer = RecordWriter::create(&spec, &self.input_paths)?;
+                self.files.push(path.to_string());
+                writer
+            }
+        };
+
+        let (index, _) = self.open.insert_full(path.to_string(), writer);
+        Ok(&mut self.open[index])
+    }
+
+    /// 🔹 가장 오래 쓰지 않은 파일을 잠시 닫음
+    fn evict(&mut self) -> Result<(), String> {
+        let Some((path, writer)) = self.open.shift_remove_index(0) else {
+            return Ok(());
+        };
+        if !writer.can_resume() {
+            return Err(format!(
+                "Too many output files open at once (at most {}); '{}' cannot be reopened to continue writing",
+                MAX_OPEN_FILES,
+                writer.path()
+            ));
+        }
+        self.suspended.insert(path, writer.suspend()?);
+        Ok(())
+    }
+}
+
+/// 🔧 경로 템플릿의 자리 표시자 이름 목록 (`{category}`, `{index}` 등)
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = &rest[start + 1..start + len];
+        if is_placeholder(name) {
+            names.push(name);
+        }
+        rest = &rest[start + len + 1..];
+    }
+    names
+}
+
+/// 🔧 자리 표시자로 쓸 수 있는 이름 (영문, 숫자, `_`, `.`)
+fn is_placeholder(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// 🔧 경로 템플릿 채우기 (`{index}` → 번호, 그 밖의 자리 표시자 → 파티션 값)
+fn render_path(template: &str, key: &str, index: usize) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = &rest[start + 1..start + len];
+
+        out.push_str(&rest[..start]);
+        match name {
+            "index" => out.push_str(&format!("{:05}", index)),
+            name if is_placeholder(name) => out.push_str(&sanitize(key)),
+            _ => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 🔧 파티션 값을 파일 이름에 쓸 수 있게 정리
+///
+/// 경로 구분자 등은 `_` 로 바꾸고, 빈 값과 `.` / `..` 은 `_` 로 대체 (다른 디렉터리로 벗어나지 않도록)
+/// 정리한 결과가 같은 값들은 같은 파일에 기록됨
+fn sanitize(key: &str) -> String {
+    let cleaned: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "-_.=@+ ".contains(c) { c } else { '_' })
+        .collect();
+    match cleaned.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => cleaned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Expression;
+    use crate::span::{Span, Spanned};
+
+    use serde_json::Value;
+
+    /// 정리한 값이 같은 파티션(`a/b`, `a_b`)이 서로의 파일을 덮어쓰지 않는지
+    #[test]
+    fn colliding_partition_keys_share_rotation_state() {
+        let dir = std::env::temp_dir().join(format!("mydsl-partition-{}", std::process::id()));
+        let template = dir.join("{c}-{index}.jsonl").to_string_lossy().into_owned();
+        let spec = OutputSpec {
+            path: template,
+            partition: Some(Spanned::new(Expression::FieldPath(vec!["c".to_string()]), Span::default())),
+            rotate: Some(Rotation::Records(1)),
+            ..OutputSpec::default()
+        };
+
+        let mut writer = PartitionedWriter::create(&spec, &[]).unwrap();
+        for (i, key) in ["a/b", "a_b", "a/b"].into_iter().enumerate() {
+            let mut record = Record::new();
+            record.insert("n".to_string(), Value::from(i));
+            writer.write(Some(key), &record).unwrap();
+        }
+        let files = writer.finish().unwrap();
+
+        let lines: Vec<String> = files
+            .iter()
+            .map(|path| fs::read_to_string(path).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(lines, vec!["{\"n\":0}\n", "{\"n\":1}\n", "{\"n\":2}\n"]);
+    }
+}
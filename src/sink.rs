@@ -0,0 +1,572 @@
+//! ✅ sink.rs
+//!
+//! 출력 대상
+//! - 레코드를 받는 즉시 한 줄(jsonl) 또는 한 행(csv / tsv)으로 기록 (버퍼링된 스트림)
+//! - parquet 은 row group 단위로 기록 (columnar.rs)
+//! - json 은 하나의 배열로 기록 (여는 `[` 를 먼저 쓰고 레코드마다 이어 붙임, 전체를 모으지 않음)
+//! - 기본적으로 같은 디렉터리의 임시 파일에 쓴 뒤 성공하면 원래 경로로 교체 (atomic rename)
+//!   - 도중에 실패하면 임시 파일만 지워지고 기존 파일은 그대로 남음
+//!   - 입력과 같은 파일에 써도 읽는 도중 내용이 지워지지 않음
+//! - `append` 는 기존 파일 끝에 바로 이어 씀 (jsonl / csv / tsv, 압축 파일은 새 스트림으로 이어 붙임)
+//! - `overwrite never|always|ask` 로 기존 파일 보호
+//! - 경로가 "-" 이면 표준 출력(stdout)에 기록
+//! - 확장자(.gz, .zst, .bz2) 또는 지정한 방식으로 압축하여 기록
+//! - csv / tsv 는 중첩 객체를 점으로 이은 열 이름(`meta.score`)으로 펼쳐서 기록
+//!   - 열 순서는 `columns [...]` 옵션 (목록에 없는 키는 버림), 없으면 첫 레코드의 키 순서
+//!   - 첫 레코드의 키 순서를 쓸 때 헤더에 없는 키가 나중 레코드에 나오면 에러 (데이터가 조용히 사라지지 않도록)
+//!   - 헤더에 하위 열(`t.key`)이 있는 중첩 객체가 나중 레코드에서 null / {} 이면 하위 열을 빈 칸으로 기록
+
+use crate::columnar::ParquetEncoder;
+use crate::compression::{compress_writer, Compression, FinishWrite};
+use crate::evaluator::{value_to_string, Record};
+use crate::format::{Format, QuoteStyle};
+use crate::source::STDIN_PATH;
+use crate::parser::{OutputSpec, OverwritePolicy};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufWriter, ErrorKind, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// ✅ 표준 출력을 뜻하는 경로
+pub const STDOUT_PATH: &str = "-";
+
+/// ✅ stdout 을 읽던 쪽이 먼저 닫혔을 때의 에러 메시지 (main 에서 조용히 종료)
+pub const BROKEN_PIPE: &str = "Broken pipe on standard output";
+
+/// ✅ 형식별 인코더
+enum Encoder {
+    Jsonl(BufWriter<Box<dyn FinishWrite>>),
+    Json {
+        writer: BufWriter<Box<dyn FinishWrite>>,
+        pretty: bool,
+        written: usize, // 기록한 레코드 수 (구분자 `,` 결정용)
+    },
+    Table(Box<TableEncoder>),
+    Parquet(Box<ParquetEncoder>),
+}
+
+/// ✅ csv / tsv 인코더
+struct TableEncoder {
+    writer: csv::Writer<Box<dyn FinishWrite>>,
+    columns: Option<Vec<String>>, // 열 순서 (None 이면 첫 레코드에서 결정)
+    explicit: bool,               // `columns [...]` 로 지정했으면 나머지 키는 버림
+    header: bool,                 // 헤더 행 기록 여부
+    written: usize,               // 기록한 레코드 수
+}
+
+/// ✅ 레코드 작성기 (jsonl / json / csv / tsv / parquet)
+pub struct RecordWriter {
+    path: String,
+    temp_file: Option<TempFile>, // 완료 시 path 로 교체할 임시 파일 (stdout, append 는 없음)
+    encoder: Encoder,
+    format: Format,
+    bytes: Arc<AtomicU64>, // 파일에 실제로 기록된 바이트 수 (압축 후, rotate 기준)
+}
+
+/// ✅ 잠시 닫아 둔 출력 파일 (열린 파일 수 제한, partition.rs)
+///
+/// 스트림은 마무리했지만 임시 파일은 아직 교체하지 않은 상태
+pub struct SuspendedFile {
+    path: String,
+    temp_file: Option<TempFile>,
+}
+
+/// ✅ 기록한 바이트 수를 세는 파일
+struct CountingFile {
+    file: File,
+    bytes: Arc<AtomicU64>,
+}
+
+/// ✅ 작성 중인 임시 파일 (교체하지 못하고 버려지면 삭제)
+struct TempFile {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl RecordWriter {
+    /// 🔹 출력 파일 생성 ("-" 이면 stdout)
+    ///
+    /// - 형식, 압축 방식을 지정하지 않았으면 확장자로 결정
+    /// - append 가 아니면 임시 파일에 기록 (입력 파일과 같은 파일이어도 안전)
+    pub fn create(spec: &OutputSpec, input_paths: &[String]) -> Result<Self, String> {
+        let path = spec.path.as_str();
+        let format = spec.format.unwrap_or_else(|| Format::from_extension(path));
+
+        if !format.is_table() && !spec.table.is_empty() {
+            return Err(format!("CSV options require csv or tsv output, but '{}' is written as {:?}", path, format));
+        }
+        if format != Format::Parquet && !spec.parquet.is_empty() {
+            return Err(format!("Parquet options require parquet output, but '{}' is written as {:?}", path, format));
+        }
+
+        if path == STDOUT_PATH {
+            return Ok(Self {
+                path: path.to_string(),
+                temp_file: None,
+                encoder: Encoder::new(io::stdout(), format, spec, false)?,
+                format,
+                bytes: Arc::default(),
+            });
+        }
+
+        if spec.append {
+            if matches!(format, Format::Json | Format::JsonPretty | Format::Parquet) {
+                return Err(format!("'append' is not supported for {:?} output", format));
+            }
+            if input_paths.iter().any(|input| same_file(input, path)) {
+                return Err(format!("Cannot append to '{}' while reading it as input", path));
+            }
+
+            return Self::open_append(spec, format, path.to_string(), None);
+        }
+
+        if Path::new(path).exists() {
+            check_overwrite(path, spec.overwrite.unwrap_or(OverwritePolicy::Always), input_paths)?;
+        }
+
+        let (temp_file, file) = TempFile::create(path)?;
+        let file = CountingFile::new(file);
+        let bytes = file.bytes.clone();
+        Ok(Self {
+            path: path.to_string(),
+            temp_file: Some(temp_file),
+            encoder: Encoder::new(file, format, spec, false)?,
+            format,
+            bytes,
+        })
+    }
+
+    /// 🔹 잠시 닫아 둔 파일을 다시 열어 이어 쓰기 (csv 헤더는 다시 쓰지 않음)
+    pub fn resume(spec: &OutputSpec, suspended: SuspendedFile) -> Result<Self, String> {
+        let format = spec.format.unwrap_or_else(|| Format::from_extension(&suspended.path));
+        Self::open_append(spec, format, suspended.path, suspended.temp_file)
+    }
+
+    /// 🔹 파일 끝에 이어 쓰도록 열기 (temp_file 이 있으면 임시 파일 쪽)
+    fn open_append(
+        spec: &OutputSpec,
+        format: Format,
+        path: String,
+        temp_file: Option<TempFile>,
+    ) -> Result<Self, String> {
+        let target = temp_file.as_ref().map_or_else(|| PathBuf::from(&path), |t| t.path.clone());
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&target)
+            .map_err(|e| format!("Failed to open output file '{}': {}", path, e))?;
+        let has_content = file.metadata().is_ok_and(|m| m.len() > 0);
+
+        let file = CountingFile::new(file);
+        let bytes = file.bytes.clone();
+        Ok(Self {
+            path,
+            temp_file,
+            encoder: Encoder::new(file, format, spec, has_content)?,
+            format,
+            bytes,
+        })
+    }
+
+    /// 🔹 출력 경로
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 🔹 지금까지 파일에 기록된 바이트 수 (버퍼에 남은 양은 제외한 근사치)
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// 🔹 quarantine 등 보조 출력용 JSONL 파일 생성
+    pub fn create_jsonl(path: &str, input_paths: &[String]) -> Result<Self, String> {
+        let spec = OutputSpec {
+            path: path.to_string(),
+            format: Some(Format::Jsonl),
+            ..OutputSpec::default()
+        };
+        Self::create(&spec, input_paths)
+    }
+
+    /// 🔹 레코드 하나 기록
+    pub fn write(&mut self, record: &Record) -> Result<(), String> {
+        match &mut self.encoder {
+            Encoder::Jsonl(writer) => {
+                if let Err(e) = serde_json::to_writer(&mut *writer, record) {
+                    return Err(match e.io_error_kind() {
+                        Some(kind) => write_error(&self.path, kind.into()),
+                        None => format!("Failed to serialize record: {}", e),
+                    });
+                }
+                writeln!(writer).map_err(|e| write_error(&self.path, e))
+            }
+            Encoder::Json { writer, pretty, written } => {
+                let separator = if *written == 0 { "[\n" } else { ",\n" };
+                *written += 1;
+                write_json_element(writer, record, *pretty, separator).map_err(|e| match e.io_error_kind() {
+                    Some(kind) => write_error(&self.path, kind.into()),
+                    None => format!("Failed to serialize record: {}", e),
+                })
+            }
+            Encoder::Table(table) => table.write(record).map_err(|e| match e {
+                TableError::Io(e) => write_error(&self.path, e),
+                TableError::Message(message) => message,
+            }),
+            Encoder::Parquet(parquet) => parquet.write(record),
+        }
+    }
+
+    /// 🔹 버퍼 비우고 마무리 (임시 파일이면 원래 경로로 교체)
+    pub fn finish(self) -> Result<(), String> {
+        self.suspend()?.commit()
+    }
+
+    /// 🔹 스트림만 마무리하고 잠시 닫기 (json / parquet 은 이어 쓸 수 없으므로 에러)
+    pub fn suspend(self) -> Result<SuspendedFile, String> {
+        let Self { path, temp_file, encoder, .. } = self;
+
+        let inner = match encoder {
+            Encoder::Jsonl(writer) => writer.into_inner().map_err(|e| e.into_error()),
+            Encoder::Json { mut writer, written, .. } => {
+                let closing = if written == 0 { "[]\n" } else { "\n]\n" };
+                writer
+                    .write_all(closing.as_bytes())
+                    .and_then(|_| writer.into_inner().map_err(|e| e.into_error()))
+            }
+            Encoder::Table(table) => table.finish(),
+            // 📌 parquet 에러는 스키마 문제일 수 있으므로 I/O 에러로 감싸지 않음
+            Encoder::Parquet(parquet) => Ok(parquet.finish()?),
+        };
+        inner
+            .and_then(|inner| inner.finish())
+            .map_err(|e| write_error(&path, e))?;
+
+        Ok(SuspendedFile { path, temp_file })
+    }
+
+    /// 🔹 이어 쓸 수 있는 형식인지 (jsonl / csv / tsv)
+    pub fn can_resume(&self) -> bool {
+        matches!(self.format, Format::Jsonl | Format::Csv | Format::Tsv)
+    }
+}
+
+impl SuspendedFile {
+    /// 🔹 임시 파일을 원래 경로로 교체
+    pub fn commit(self) -> Result<(), String> {
+        match self.temp_file {
+            Some(temp_file) => temp_file.commit(&self.path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl CountingFile {
+    /// 🔹 이미 있는 내용의 크기부터 세기 시작 (append / resume)
+    fn new(file: File) -> Self {
+        let existing = file.metadata().map_or(0, |m| m.len());
+        Self {
+            file,
+            bytes: Arc::new(AtomicU64::new(existing)),
+        }
+    }
+}
+
+impl Write for CountingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.file.write(buf)?;
+        self.bytes.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl TempFile {
+    /// 🔹 대상 파일과 같은 디렉터리에 임시 파일 생성 (`.이름.<pid>.tmp`)
+    ///
+    /// 같은 파일 시스템이어야 rename 이 원자적으로 이루어짐
+    ///
+    /// 에러에는 임시 파일 이름 대신 사용자가 적은 대상 경로를 표시
+    fn create(target: &str) -> Result<(Self, File), String> {
+        let target_path = Path::new(target);
+        let name = target_path.file_name().map_or_else(|| "output".into(), |n| n.to_string_lossy());
+        let path = target_path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open output file '{}': {}", target, e))?;
+
+        Ok((Self { path, committed: false }, file))
+    }
+
+    /// 🔹 임시 파일을 대상 경로로 교체
+    fn commit(mut self, target: &str) -> Result<(), String> {
+        fs::rename(&self.path, target)
+            .map_err(|e| format!("Failed to replace output file '{}': {}", target, e))?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 🔧 기존 출력 파일을 덮어써도 되는지 확인 (overwrite 정책)
+///
+/// 입력 파일 자체를 고쳐 쓰는 경우도 기존 파일을 덮어쓰는 것으로 취급
+fn check_overwrite(path: &str, policy: OverwritePolicy, input_paths: &[String]) -> Result<(), String> {
+    match policy {
+        OverwritePolicy::Always => Ok(()),
+        OverwritePolicy::Never => Err(format!("Output file '{}' already exists (overwrite never)", path)),
+        OverwritePolicy::Ask => {
+            let stdin = io::stdin();
+            if !stdin.is_terminal() || input_paths.iter().any(|input| input == STDIN_PATH) {
+                return Err(format!(
+                    "Output file '{}' already exists and cannot ask for confirmation (stdin is not a terminal)",
+                    path
+                ));
+            }
+
+            eprint!("❓ Output file '{}' already exists. Overwrite? [y/N] ", path);
+            let mut answer = String::new();
+            stdin
+                .lock()
+                .read_line(&mut answer)
+                .map_err(|e| format!("Failed to read answer: {}", e))?;
+
+            match answer.trim().to_ascii_lowercase().as_str() {
+                "y" | "yes" => Ok(()),
+                _ => Err(format!("Output file '{}' already exists (not overwritten)", path)),
+            }
+        }
+    }
+}
+
+impl Encoder {
+    /// 🔹 형식에 맞는 인코더 생성
+    ///
+    /// 압축 방식을 지정하지 않았으면 확장자로 결정, parquet 은 스트림 대신 내부 압축으로 적용
+    /// `has_content` 는 append 대상 파일에 이미 내용이 있는 경우로, csv 헤더를 다시 쓰지 않음
+    fn new<W: Write + Send + 'static>(
+        writer: W,
+        format: Format,
+        spec: &OutputSpec,
+        has_content: bool,
+    ) -> Result<Self, String> {
+        if format == Format::Parquet {
+            let writer = compress_writer(writer, Compression::None, None)?;
+            let parquet = ParquetEncoder::new(writer, &spec.parquet, spec.compression, spec.level)?;
+            return Ok(Encoder::Parquet(Box::new(parquet)));
+        }
+
+        let compression = spec.compression.unwrap_or_else(|| Compression::from_extension(&spec.path));
+        let writer = compress_writer(writer, compression, spec.level)?;
+        let options = &spec.table;
+
+        match format {
+            Format::Jsonl => return Ok(Encoder::Jsonl(BufWriter::new(writer))),
+            Format::Json | Format::JsonPretty => {
+                return Ok(Encoder::Json {
+                    writer: BufWriter::new(writer),
+                    pretty: format == Format::JsonPretty,
+                    written: 0,
+                });
+            }
+            Format::Csv | Format::Tsv | Format::Parquet => {}
+        }
+
+        let quote = match options.quote.unwrap_or(QuoteStyle::Necessary) {
+            QuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+            QuoteStyle::Always => csv::QuoteStyle::Always,
+            QuoteStyle::NonNumeric => csv::QuoteStyle::NonNumeric,
+            QuoteStyle::Never => csv::QuoteStyle::Never,
+        };
+        let writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter.unwrap_or(format.default_delimiter()))
+            .quote_style(quote)
+            .has_headers(false)
+            .from_writer(writer);
+
+        Ok(Encoder::Table(Box::new(TableEncoder {
+            writer,
+            columns: options.columns.clone(),
+            explicit: options.columns.is_some(),
+            header: options.header.unwrap_or(true) && !has_content,
+            written: 0,
+        })))
+    }
+}
+
+/// ✅ csv / tsv 기록 에러
+enum TableError {
+    Io(io::Error),
+    Message(String),
+}
+
+impl From<csv::Error> for TableError {
+    fn from(e: csv::Error) -> Self {
+        match e.into_kind() {
+            csv::ErrorKind::Io(e) => TableError::Io(e),
+            other => TableError::Message(format!("Failed to write CSV row: {:?}", other)),
+        }
+    }
+}
+
+impl TableEncoder {
+    /// 🔹 레코드를 펼쳐서 한 행 기록 (첫 레코드 전에 헤더 기록)
+    fn write(&mut self, record: &Record) -> Result<(), TableError> {
+        let mut cells = IndexMap::new();
+        let mut empty = Vec::new();
+        for (key, value) in record {
+            flatten(key.clone(), value, &mut cells, &mut empty);
+        }
+
+        if self.written == 0 {
+            self.write_header(cells.keys())?;
+        }
+        self.written += 1;
+
+        let columns = self.columns.as_deref().unwrap_or_default();
+        // null / {} 인 중첩 객체는 헤더에 하위 열(`key.xxx`)이 있으면 그 열들을 빈 칸으로 둠
+        let is_empty_object = |key: &String| {
+            empty.contains(key) && columns.iter().any(|column| column.starts_with(&format!("{}.", key)))
+        };
+        if !self.explicit
+            && let Some(extra) = cells
+                .keys()
+                .find(|key| !columns.contains(key) && !is_empty_object(key))
+        {
+            return Err(TableError::Message(format!(
+                "Record {} has column '{}' that is not in the CSV header (set the order with `columns [...]`)",
+                self.written, extra
+            )));
+        }
+
+        let row = columns
+            .iter()
+            .map(|column| cells.get(column).map_or("", String::as_str));
+        self.writer.write_record(row)?;
+        Ok(())
+    }
+
+    /// 🔹 열 순서 확정 및 헤더 기록 (`columns` 가 없으면 첫 레코드의 키 순서)
+    fn write_header<'a>(&mut self, keys: impl Iterator<Item = &'a String>) -> Result<(), TableError> {
+        let columns = self
+            .columns
+            .get_or_insert_with(|| keys.cloned().collect());
+        if self.header {
+            self.writer.write_record(columns.iter())?;
+        }
+        Ok(())
+    }
+
+    /// 🔹 남은 행 기록 후 내부 스트림 반환 (레코드가 없어도 `columns` 가 있으면 헤더는 기록)
+    fn finish(mut self) -> io::Result<Box<dyn FinishWrite>> {
+        if self.written == 0 && self.columns.is_some() {
+            self.write_header(std::iter::empty()).map_err(|e| match e {
+                TableError::Io(e) => e,
+                TableError::Message(message) => io::Error::other(message),
+            })?;
+        }
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+/// 🔧 json 배열의 원소 하나 기록 (pretty 면 배열 안쪽으로 한 단계 들여쓰기)
+fn write_json_element(
+    writer: &mut impl Write,
+    record: &Record,
+    pretty: bool,
+    separator: &str,
+) -> serde_json::Result<()> {
+    writer.write_all(separator.as_bytes()).map_err(serde_json::Error::io)?;
+    if !pretty {
+        return serde_json::to_writer(writer, record);
+    }
+
+    let mut buffer = Vec::new();
+    serde_json::to_writer_pretty(&mut buffer, record)?;
+
+    // 문자열 안의 줄바꿈은 `\n` 으로 이스케이프되므로 실제 개행 뒤에만 들여쓰기를 넣으면 됨
+    writer.write_all(b"  ").map_err(serde_json::Error::io)?;
+    for (i, line) in buffer.split(|&b| b == b'\n').enumerate() {
+        if i > 0 {
+            writer.write_all(b"\n  ").map_err(serde_json::Error::io)?;
+        }
+        writer.write_all(line).map_err(serde_json::Error::io)?;
+    }
+    Ok(())
+}
+
+/// 🔧 중첩 객체를 점으로 이은 열 이름으로 펼치기 (`{"meta": {"score": 1}}` → `meta.score = "1"`)
+///
+/// 배열은 JSON 문자열, null 은 빈 칸
+/// null 이나 빈 객체인 열 이름은 `empty` 에도 기록 (다른 레코드에서 펼쳐진 하위 열과 맞추기 위해)
+fn flatten(key: String, value: &Value, cells: &mut IndexMap<String, String>, empty: &mut Vec<String>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (child, value) in map {
+                flatten(format!("{}.{}", key, child), value, cells, empty);
+            }
+        }
+        other => {
+            if other.is_null() || other.as_object().is_some_and(|map| map.is_empty()) {
+                empty.push(key.clone());
+            }
+            cells.insert(key, value_to_string(other));
+        }
+    }
+}
+
+/// 🔧 print / print line 출력 (stdout 에 JSON 한 줄)
+///
+/// stdout 을 읽던 쪽이 먼저 닫히면 output "-" 와 마찬가지로 BROKEN_PIPE 에러
+pub fn print_record(record: &Record) -> Result<(), String> {
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = serde_json::to_writer(&mut stdout, record) {
+        return Err(match e.io_error_kind() {
+            Some(kind) => write_error(STDOUT_PATH, kind.into()),
+            None => format!("Failed to serialize record: {}", e),
+        });
+    }
+    writeln!(stdout).map_err(|e| write_error(STDOUT_PATH, e))
+}
+
+/// 🔧 쓰기 에러 메시지
+///
+/// stdout 을 읽던 쪽이 먼저 닫힌 경우(`mydsl x.jdl | head`)는 BROKEN_PIPE
+/// (인터프리터가 나머지 출력을 마무리한 뒤 main 에서 조용히 종료)
+fn write_error(path: &str, e: io::Error) -> String {
+    if path == STDOUT_PATH && e.kind() == ErrorKind::BrokenPipe {
+        return BROKEN_PIPE.to_string();
+    }
+    format!("Failed to write to output file: {}", e)
+}
+
+/// 🔧 stdout 이 닫혀서 난 에러인지 확인
+pub fn is_broken_pipe(message: &str) -> bool {
+    message == BROKEN_PIPE
+}
+
+/// 🔧 두 경로가 같은 파일을 가리키는지 확인
+fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => Path::new(a) == Path::new(b),
+    }
+}
@@ -0,0 +1,384 @@
+//! ✅ source.rs
+//!
+//! 입력 데이터 소스
+//! - JSONL 파일을 한 줄씩 읽어 레코드 단위로 넘겨줌 (전체를 메모리에 올리지 않음)
+//! - 잘못된 줄은 줄 번호, 바이트 오프셋, 원본 텍스트와 함께 보고 (처리 방식은 on_error 정책)
+//! - 빈 줄(공백만 있는 줄) 무시, 파일 맨 앞 UTF-8 BOM 제거, CRLF 줄바꿈 허용
+//! - UTF-8 이 아닌 줄도 잘못된 줄로 취급 (원본 텍스트는 깨진 바이트를 U+FFFD 로 바꿔 보관)
+//! - wrap_values 옵션: 객체가 아닌 줄(배열, 숫자, 문자열 등)을 {"value": ...} 로 감싸서 읽음
+//! - 경로가 "-" 이면 표준 입력(stdin)에서 읽음
+//! - gzip / zstd / bzip2 압축 파일은 자동으로 해제 (줄 번호, 바이트 오프셋은 해제 후 기준)
+//! - csv / tsv 는 헤더의 열 이름을 레코드 키로 사용 (`without header` 면 column1, column2, ...)
+//!   - 점이 들어간 열 이름(`meta.score`)은 중첩 객체로 복원, 값은 모두 문자열
+
+use crate::compression::decompress_reader;
+use crate::evaluator::Record;
+use crate::format::{Format, TableOptions};
+use crate::parser::InputSpec;
+
+use serde_json::{Map, Value};
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// ✅ 표준 입력을 뜻하는 경로
+pub const STDIN_PATH: &str = "-";
+
+/// ✅ JSON 으로 해석할 수 없는 입력 줄
+#[derive(Debug)]
+pub struct MalformedLine {
+    pub line_number: usize, // 줄 번호 (1부터)
+    pub offset: usize,      // 줄 시작 바이트 오프셋
+    pub raw: String,        // 원본 텍스트 (개행 제외)
+    pub reason: String,     // 파싱 에러 내용
+}
+
+/// ✅ 입력 읽기 에러
+#[derive(Debug)]
+pub enum ReadError {
+    Io(String),                // 파일 읽기 자체 실패 → 항상 중단
+    Malformed(MalformedLine),  // 잘못된 줄 → on_error 정책에 따라 처리
+}
+
+impl ReadError {
+    /// 🔹 사용자에게 보여줄 메시지
+    pub fn message(&self, path: &str) -> String {
+        match self {
+            ReadError::Io(message) => message.clone(),
+            ReadError::Malformed(line) => format!(
+                "Parsing error in '{}' line {} (byte offset {}): {}",
+                path, line.line_number, line.offset, line.reason
+            ),
+        }
+    }
+}
+
+/// ✅ 형식에 따라 레코드를 읽는 리더
+pub enum RecordReader {
+    Jsonl(JsonlReader),
+    Table(TableReader),
+}
+
+impl RecordReader {
+    /// 🔹 input 설정에 맞는 리더 열기 (형식을 지정하지 않았으면 확장자로 판단)
+    ///
+    /// json 배열은 출력 전용이므로 `.json` 확장자 파일은 jsonl 로 읽음
+    pub fn open(path: &str, spec: &InputSpec) -> Result<Self, String> {
+        let format = match spec.format {
+            Some(Format::Json | Format::JsonPretty) => {
+                return Err("JSON array input is not supported (use jsonl)".to_string());
+            }
+            Some(format) => format,
+            None => Format::from_extension(path),
+        };
+        if format == Format::Parquet {
+            return Err("Parquet input is not supported".to_string());
+        }
+
+        if format.is_table() {
+            if spec.wrap_values {
+                return Err("'wrap_values' is only supported for jsonl input".to_string());
+            }
+            return Ok(RecordReader::Table(TableReader::open(path, format, &spec.table)?));
+        }
+
+        if !spec.table.is_empty() {
+            return Err(format!("CSV options require csv or tsv input, but '{}' is read as jsonl", path));
+        }
+        Ok(RecordReader::Jsonl(JsonlReader::open(path, spec.wrap_values)?))
+    }
+
+    /// 🔹 마지막으로 읽은 레코드의 줄 번호 (1부터)
+    pub fn line_number(&self) -> usize {
+        match self {
+            RecordReader::Jsonl(reader) => reader.line_number(),
+            RecordReader::Table(reader) => reader.line_number(),
+        }
+    }
+
+    /// 🔹 다음 레코드 읽기 (파일 끝이면 None)
+    pub fn next_record(&mut self) -> Option<Result<Record, ReadError>> {
+        match self {
+            RecordReader::Jsonl(reader) => reader.next_record(),
+            RecordReader::Table(reader) => reader.next_record(),
+        }
+    }
+}
+
+/// ✅ JSONL 파일을 한 줄씩 읽는 리더
+pub struct JsonlReader {
+    reader: Box<dyn BufRead>,
+    line: Vec<u8>,      // 줄 버퍼 (재사용, UTF-8 검사 전 원본 바이트)
+    line_number: usize, // 마지막으로 읽은 줄 번호 (1부터)
+    offset: usize,      // 다음 줄의 시작 바이트 오프셋
+    wrap_values: bool,  // 객체가 아닌 값을 {"value": ...} 로 감쌀지 여부
+}
+
+impl JsonlReader {
+    /// 🔹 파일 열기 ("-" 이면 stdin)
+    pub fn open(path: &str, wrap_values: bool) -> Result<Self, String> {
+        Ok(Self {
+            reader: open_stream(path)?,
+            line: Vec::new(),
+            line_number: 0,
+            offset: 0,
+            wrap_values,
+        })
+    }
+
+    /// 🔹 마지막으로 읽은 줄 번호 (1부터)
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// 🔹 다음 레코드 읽기 (파일 끝이면 None)
+    pub fn next_record(&mut self) -> Option<Result<Record, ReadError>> {
+        loop {
+            self.line.clear();
+            let line_offset = self.offset;
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(n) => {
+                    self.line_number += 1;
+                    self.offset += n;
+                }
+                Err(e) => return Some(Err(ReadError::Io(format!("Failed to read line: {}", e)))),
+            }
+
+            let line = match std::str::from_utf8(&self.line) {
+                Ok(line) => line,
+                Err(e) => {
+                    return Some(Err(ReadError::Malformed(MalformedLine {
+                        line_number: self.line_number,
+                        offset: line_offset,
+                        raw: String::from_utf8_lossy(&self.line).trim_end_matches(['\n', '\r']).to_string(),
+                        reason: format!("invalid UTF-8 at byte {} of the line", e.valid_up_to()),
+                    })));
+                }
+            };
+            let mut line = line.trim_end_matches(['\n', '\r']);
+            if self.line_number == 1 {
+                line = line.strip_prefix('\u{FEFF}').unwrap_or(line);
+            }
+
+            // 공백만 있는 줄은 건너뜀
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(self.parse_line(line).map_err(|reason| {
+                ReadError::Malformed(MalformedLine {
+                    line_number: self.line_number,
+                    offset: line_offset,
+                    raw: line.to_string(),
+                    reason,
+                })
+            }));
+        }
+    }
+
+    /// 🔹 한 줄을 레코드로 변환 (wrap_values 면 객체가 아닌 값도 허용)
+    fn parse_line(&self, line: &str) -> Result<Record, String> {
+        let error = match serde_json::from_str::<Record>(line) {
+            Ok(record) => return Ok(record),
+            Err(e) => e,
+        };
+
+        if !self.wrap_values {
+            return Err(error.to_string());
+        }
+
+        match serde_json::from_str::<Value>(line) {
+            Ok(value) if !value.is_object() => {
+                let mut record = Record::new();
+                record.insert("value".to_string(), value);
+                Ok(record)
+            }
+            _ => Err(error.to_string()),
+        }
+    }
+}
+
+/// ✅ csv / tsv 파일을 한 행씩 읽는 리더
+pub struct TableReader {
+    reader: csv::Reader<Box<dyn BufRead>>,
+    row: csv::StringRecord,       // 행 버퍼 (재사용)
+    columns: Option<Vec<String>>, // 열 이름 (헤더가 없으면 첫 행의 칸 수로 결정)
+    delimiter: u8,                // 잘못된 행의 원본 텍스트 복원용
+    line_number: usize,           // 마지막으로 읽은 행의 시작 줄 번호 (1부터)
+}
+
+impl TableReader {
+    /// 🔹 파일 열기 ("-" 이면 stdin), 헤더가 있으면 바로 읽음
+    pub fn open(path: &str, format: Format, options: &TableOptions) -> Result<Self, String> {
+        let delimiter = options.delimiter.unwrap_or(format.default_delimiter());
+        let reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(open_stream(path)?);
+
+        let mut table = Self {
+            reader,
+            row: csv::StringRecord::new(),
+            columns: None,
+            delimiter,
+            line_number: 0,
+        };
+
+        // 출력의 기본값(헤더 기록)과 맞춰 기본적으로 첫 행을 헤더로 읽음
+        if options.header.unwrap_or(true) {
+            let header = match table.read_row() {
+                Some(Ok(())) => &table.row,
+                Some(Err(e)) => return Err(format!("Failed to read header: {}", e.message(path))),
+                None => return Ok(table),
+            };
+            let columns = header
+                .iter()
+                .enumerate()
+                .map(|(i, name)| match i {
+                    0 => name.trim_start_matches('\u{FEFF}').to_string(),
+                    _ => name.to_string(),
+                })
+                .collect();
+            table.columns = Some(columns);
+        }
+
+        Ok(table)
+    }
+
+    /// 🔹 마지막으로 읽은 행의 줄 번호 (1부터)
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// 🔹 다음 레코드 읽기 (파일 끝이면 None)
+    pub fn next_record(&mut self) -> Option<Result<Record, ReadError>> {
+        if let Err(e) = self.read_row()? {
+            return Some(Err(e));
+        }
+
+        let columns = self.columns.get_or_insert_with(|| {
+            (1..=self.row.len()).map(|i| format!("column{}", i)).collect()
+        });
+
+        if self.row.len() != columns.len() {
+            return Some(Err(ReadError::Malformed(MalformedLine {
+                line_number: self.line_number,
+                offset: self.row.position().map_or(0, |p| p.byte() as usize),
+                raw: self.row.iter().collect::<Vec<_>>().join(&(self.delimiter as char).to_string()),
+                reason: format!("expected {} column(s), but found {}", columns.len(), self.row.len()),
+            })));
+        }
+
+        let mut record = Record::new();
+        for (name, value) in columns.iter().zip(self.row.iter()) {
+            insert_dotted(&mut record, name, Value::from(value));
+        }
+        Some(Ok(record))
+    }
+
+    /// 🔹 한 행을 버퍼로 읽기 (파일 끝이면 None)
+    fn read_row(&mut self) -> Option<Result<(), ReadError>> {
+        match self.reader.read_record(&mut self.row) {
+            Ok(false) => None,
+            Ok(true) => {
+                self.line_number = self.row.position().map_or(0, |p| p.line() as usize);
+                Some(Ok(()))
+            }
+            Err(e) if matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                Some(Err(ReadError::Io(format!("Failed to read line: {}", e))))
+            }
+            Err(e) => {
+                // UTF-8 가 아닌 칸 등: 해당 행만 잘못된 줄로 처리
+                let position = e.position().cloned();
+                self.line_number = position.as_ref().map_or(self.line_number + 1, |p| p.line() as usize);
+                Some(Err(ReadError::Malformed(MalformedLine {
+                    line_number: self.line_number,
+                    offset: position.map_or(0, |p| p.byte() as usize),
+                    raw: String::new(),
+                    reason: e.to_string(),
+                })))
+            }
+        }
+    }
+}
+
+/// 🔧 점으로 구분된 열 이름을 중첩 객체로 넣기 (`meta.score` → {"meta": {"score": ...}})
+///
+/// 중간 경로가 객체가 아닌 값으로 이미 있거나 빈 구간이 있으면 열 이름 그대로 사용
+fn insert_dotted(record: &mut Record, name: &str, value: Value) {
+    let parts: Vec<&str> = name.split('.').collect();
+    if parts.len() == 1 || parts.iter().any(|part| part.is_empty()) {
+        record.insert(name.to_string(), value);
+        return;
+    }
+
+    let root = record
+        .entry(parts[0].to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+
+    let mut current = root;
+    for part in &parts[1..parts.len() - 1] {
+        let Value::Object(map) = current else { break };
+        current = map
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    match current {
+        Value::Object(map) if !map.contains_key(parts[parts.len() - 1]) => {
+            map.insert(parts[parts.len() - 1].to_string(), value);
+        }
+        _ => {
+            record.insert(name.to_string(), value);
+        }
+    }
+}
+
+/// 🔧 입력 스트림 열기 ("-" 이면 stdin, 압축은 자동 해제)
+fn open_stream(path: &str) -> Result<Box<dyn BufRead>, String> {
+    let reader = if path == STDIN_PATH {
+        decompress_reader(io::stdin().lock(), path)
+    } else {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open file '{}': {}", path, e))?;
+        decompress_reader(BufReader::new(file), path)
+    };
+    reader.map_err(|e| format!("Failed to read file '{}': {}", path, e))
+}
+
+/// 🔧 입력 경로 목록 확장
+/// - glob 패턴(`*`, `?`, `[...]` 포함)은 일치하는 파일을 이름순으로 정렬해 펼침
+/// - 일반 경로와 "-" 는 그대로 사용
+/// - 적힌 순서를 유지하므로 실행할 때마다 같은 순서로 읽음
+pub fn expand_input_paths(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut paths = Vec::new();
+
+    for pattern in patterns {
+        if pattern == STDIN_PATH || !pattern.contains(['*', '?', '[']) {
+            paths.push(pattern.clone());
+            continue;
+        }
+
+        let entries = glob::glob(pattern)
+            .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+
+        let mut matched = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read '{}': {}", pattern, e))?;
+            if entry.is_file() {
+                matched.push(entry.to_string_lossy().into_owned());
+            }
+        }
+
+        if matched.is_empty() {
+            return Err(format!("No input files match '{}'", pattern));
+        }
+
+        matched.sort();
+        paths.extend(matched);
+    }
+
+    Ok(paths)
+}
@@ -0,0 +1,37 @@
+//! ✅ span.rs
+//!
+//! 스크립트 상의 위치 정보(Span) 정의
+//! - 토큰, 명령어, 표현식이 소스의 어느 위치에서 왔는지 기록
+//! - 파서/런타임 에러를 `script.jdl:7:23` 형태로 보고하는 데 사용
+
+/// ✅ 소스 내 구간 (바이트 오프셋 + 시작 줄/열)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,  // 시작 바이트 오프셋
+    pub end: usize,    // 끝 바이트 오프셋 (미포함)
+    pub line: usize,   // 시작 줄 번호 (1부터)
+    pub column: usize, // 시작 열 번호 (1부터, 문자 단위)
+}
+
+impl Span {
+    /// 🔹 self 시작부터 other 끝까지 포함하는 Span
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..self
+        }
+    }
+}
+
+/// ✅ 위치 정보가 붙은 노드 (Token, Command, Expression 등)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Self { node, span }
+    }
+}